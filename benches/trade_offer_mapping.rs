@@ -1,5 +1,6 @@
 use steam_tradeoffer_manager::types::{AppId, ClassId, InstanceId};
 use steam_tradeoffer_manager::ClassInfoCache;
+use steam_tradeoffer_manager::enums::Language;
 use steam_tradeoffer_manager::response::ClassInfo;
 use steam_tradeoffer_manager::api::response::RawTradeOffer;
 use steam_tradeoffer_manager::error::FileError;
@@ -51,7 +52,6 @@ fn get_offers() -> Vec<RawTradeOffer> {
         pub trade_offers_sent: Vec<RawTradeOffer>,
         #[serde(default)]
         pub trade_offers_received: Vec<RawTradeOffer>,
-        pub next_cursor: Option<u32>,
     }
     
     #[derive(Deserialize, Debug)]
@@ -94,7 +94,7 @@ fn get_classinfo_cache(
         .collect::<HashMap<_, _>>();
     let classinfo_cache = ClassInfoCache::with_capacity(500);
     
-    classinfo_cache.insert_map(classes);
+    classinfo_cache.insert_map(Language::English, classes);
     classinfo_cache
 }
 
@@ -102,7 +102,7 @@ fn get_map(
     classes: &[ClassInfoClass],
     classinfo_cache: &ClassInfoCache,
 ) -> ClassInfoMap {
-    let (map, _misses) = classinfo_cache.get_map(classes);
+    let (map, _misses) = classinfo_cache.get_map(Language::English, classes);
     map
 }
 
@@ -2,7 +2,7 @@ use steam_tradeoffer_manager::TradeOfferManager;
 use steam_tradeoffer_manager::response::{TradeOffer, Asset};
 use steam_tradeoffer_manager::enums::TradeOfferState;
 use steam_tradeoffer_manager::error::Error;
-use steam_tradeoffer_manager::polling::PollOptions;
+use steam_tradeoffer_manager::polling::{PollOptions, PollEvent, RecvError};
 use chrono::Duration;
 use owo_colors::OwoColorize;
 
@@ -74,32 +74,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     })?;
     
     // Listen to the receiver for events.
-    while let Some(message) = rx.recv().await {
-        match message {
-            Ok(offers) => {
-                for (mut offer, old_state) in offers {
-                    if let Some(state) = old_state {
-                        println!(
-                            "{} Offer changed state: {state} -> {}",
-                            offer.bright_magenta().bold(),
-                            offer.trade_offer_state,
-                        );
-                    }
-                    
-                    // Skip offers that are ours.
-                    if offer.is_our_offer {
-                        continue;
-                    }
-                    
-                    if offer.trade_offer_state == TradeOfferState::Active {
-                        accept_free_items(&manager, &mut offer).await;
-                    }
-                }
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(count)) => {
+                println!("Skipped {count} events");
+                continue;
             },
-            Err(error) => {
+            // Polling stopped.
+            Err(RecvError::Closed) => break,
+        };
+        
+        match event {
+            PollEvent::NewReceivedOffer(
+                mut offer,
+            ) if offer.trade_offer_state == TradeOfferState::Active => {
+                accept_free_items(&manager, &mut offer).await;
+            },
+            PollEvent::ReceivedOfferChanged { offer, old_state } |
+            PollEvent::SentOfferChanged { offer, old_state } => {
+                println!(
+                    "{} Offer changed state: {old_state} -> {}",
+                    offer.bright_magenta().bold(),
+                    offer.trade_offer_state,
+                );
+            },
+            PollEvent::AutoCancelled { offer, .. } => {
+                println!("{} Offer cancelled", offer.bright_magenta().bold());
+            },
+            PollEvent::PollFailed(error) => {
                 // If an error occurred during the poll.
                 println!("Error encountered polling offers: {error}");
             },
+            _ => {},
         }
     }
     
@@ -1,12 +1,16 @@
 use super::SteamTradeOfferAPI;
 use crate::helpers::USER_AGENT_STRING;
+use crate::helpers::{DEFAULT_COMMUNITY_URL, DEFAULT_WEB_API_URL};
+use crate::{RateLimiter, RetryPolicy};
 use crate::helpers::default_data_directory;
 use crate::ClassInfoCache;
+use crate::classinfo_store::ClassInfoStore;
 use crate::enums::Language;
 use std::path::PathBuf;
 use std::sync::Arc;
 use reqwest::cookie::Jar;
 use reqwest_middleware::ClientWithMiddleware;
+use url::Url;
 
 /// Builder for constructing a [`SteamTradeOfferAPI`].
 #[derive(Debug, Clone)]
@@ -20,12 +24,22 @@ pub struct SteamTradeOfferAPIBuilder {
     pub(crate) classinfo_cache: Option<ClassInfoCache>,
     /// The location to save data to.
     pub(crate) data_directory: PathBuf,
+    /// The storage backend for classinfos.
+    pub(crate) classinfo_store: Option<Arc<dyn ClassInfoStore>>,
     /// Request cookies.
     pub(crate) cookie_jar: Option<Arc<Jar>>,
     /// Client to use for requests. Remember to also include the cookies connected to this client.
     pub(crate) client: Option<ClientWithMiddleware>,
     /// User agent for requests.
     pub(crate) user_agent: &'static str,
+    /// The base URL for Steam Community requests.
+    pub(crate) community_url: Url,
+    /// The base URL for Steam Web API requests.
+    pub(crate) web_api_url: Url,
+    /// Limits the rate of requests sent by the default client.
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    /// Retries requests which fail due to transient errors.
+    pub(crate) retry_policy: Option<RetryPolicy>,
 }
 
 impl Default for SteamTradeOfferAPIBuilder {
@@ -42,9 +56,14 @@ impl SteamTradeOfferAPIBuilder {
             language: Language::English,
             classinfo_cache: None,
             data_directory: default_data_directory(),
+            classinfo_store: None,
             cookie_jar: None,
             client: None,
             user_agent: USER_AGENT_STRING,
+            community_url: DEFAULT_COMMUNITY_URL.clone(),
+            web_api_url: DEFAULT_WEB_API_URL.clone(),
+            rate_limiter: None,
+            retry_policy: None,
         }
     }
     
@@ -78,6 +97,17 @@ impl SteamTradeOfferAPIBuilder {
         self
     }
     
+    /// The storage backend for classinfos. Defaults to a 
+    /// [`FileClassInfoStore`][crate::classinfo_store::FileClassInfoStore] which stores each 
+    /// classinfo as a file in the data directory.
+    pub fn classinfo_store<T>(mut self, classinfo_store: T) -> Self
+    where
+        T: ClassInfoStore + 'static,
+    {
+        self.classinfo_store = Some(Arc::new(classinfo_store));
+        self
+    }
+    
     /// Client to use for requests. It is also required to include the associated cookies with this
     /// client so that the `set_cookies` method works as expected.
     pub fn client(mut self, client: ClientWithMiddleware, cookies: Arc<Jar>) -> Self {
@@ -86,6 +116,41 @@ impl SteamTradeOfferAPIBuilder {
         self
     }
     
+    /// Limits the rate of requests for trade offers, inventories, and classinfos, using separate 
+    /// budgets for inventories, other Steam Community requests, and the Steam Web API. Requests 
+    /// are not limited if this is not set. See [`RateLimiter`] for sharing budgets and for using 
+    /// it with a client provided using `client`.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+    
+    /// Retries getting trade offers, inventories, classinfos, receipts, and trade history when 
+    /// the request fails due to a transient error. Sending, accepting, declining, and cancelling 
+    /// offers are never retried. Requests are not retried if this is not set. This has no effect 
+    /// when a client is provided using `client`; add the [`RetryPolicy`] to the client's 
+    /// middleware instead.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+    
+    /// The base URL for Steam Community requests, which include sending and responding to offers, 
+    /// inventories, and trade receipts. Default is `https://steamcommunity.com`. Useful for sending 
+    /// requests to a proxy or a local server, e.g. `http://127.0.0.1:8080`.
+    pub fn community_url(mut self, community_url: Url) -> Self {
+        self.community_url = community_url;
+        self
+    }
+    
+    /// The base URL for Steam Web API requests, such as getting trade offers. Default is 
+    /// `https://api.steampowered.com`. Useful for sending requests to a proxy or a local server, 
+    /// e.g. `http://127.0.0.1:8080`.
+    pub fn web_api_url(mut self, web_api_url: Url) -> Self {
+        self.web_api_url = web_api_url;
+        self
+    }
+    
     /// Builds the [`SteamTradeOfferAPI`].
     pub fn build(self) -> SteamTradeOfferAPI {
         self.into()
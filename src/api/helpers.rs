@@ -1,14 +1,16 @@
 use super::response as api_response;
-use super::SteamTradeOfferAPI;
 use crate::error::{MissingClassInfoError, ParseHtmlError, ParameterError};
 use crate::SteamID;
+use crate::helpers::join_url;
 use crate::types::ClassInfoMap;
 use crate::response::{self, User, UserDetails};
 use std::sync::Arc;
 use lazy_regex::Regex;
 use lazy_regex::regex_captures;
+use url::Url;
 
 pub fn offer_referer_url(
+    community_url: &Url,
     pathname: &str,
     partner: SteamID,
     token: &Option<&str>,
@@ -21,13 +23,42 @@ pub fn offer_referer_url(
         params.push(("token", token.to_string()));
     }
     
-    let url = SteamTradeOfferAPI::get_url(&format!("/tradeoffer/{pathname}"));
+    let url = join_url(community_url, &format!("/tradeoffer/{pathname}"));
     let url = reqwest::Url::parse_with_params(&url, &params)
         .map_err(ParameterError::UrlParse)?;
     
     Ok(url.into())
 }
 
+/// Maps trade offers with the given descriptions. Offers with missing descriptions are returned 
+/// separately in the 2nd part of the tuple.
+pub fn map_raw_trade_offers_with_descriptions(
+    offers: Vec<api_response::RawTradeOffer>,
+    map: ClassInfoMap,
+) -> (Vec<response::TradeOffer>, Vec<api_response::UnmappedTradeOffer>) {
+    let mut mapped = Vec::with_capacity(offers.len());
+    let mut unmapped = Vec::new();
+    
+    for offer in offers {
+        // offers where the classinfo cannot be obtained do not cause the whole request to fail
+        if let Some(error) = offer.find_missing_classinfo(&map) {
+            unmapped.push(api_response::UnmappedTradeOffer {
+                offer,
+                error,
+            });
+            continue;
+        }
+        
+        match offer.try_combine_classinfos(&map) {
+            Ok(offer) => mapped.push(offer),
+            // All classinfos were checked above so this should not happen.
+            Err(error) => log::warn!("Error combining classinfos: {error}"),
+        }
+    }
+    
+    (mapped, unmapped)
+}
+
 pub fn from_raw_receipt_asset(
     asset: api_response::RawReceiptAsset,
     map: &ClassInfoMap,
@@ -53,12 +84,7 @@ pub fn parse_user_details(
 ) -> Result<UserDetails, ParseHtmlError> {
     fn get_days(group: Option<(&str, &str)>) -> u32 {
         match group {
-            Some((_, days_str)) => {
-                match days_str.parse::<u32>() {
-                    Ok(days) => days,
-                    Err(_e) => 0,
-                }
-            },
+            Some((_, days_str)) => days_str.parse::<u32>().unwrap_or_default(),
             None => 0,
         }
     }
@@ -117,6 +143,7 @@ pub fn parse_receipt_script(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::helpers::DEFAULT_COMMUNITY_URL;
     
     #[test]
     fn parses_receipt_script_correctly() {
@@ -150,6 +177,7 @@ mod tests {
     #[test]
     fn gets_offer_referer_url() {
         let url = offer_referer_url(
+            &DEFAULT_COMMUNITY_URL,
             "new",
             SteamID::from(76561198000000000), 
             &Some("token"),
@@ -158,6 +186,7 @@ mod tests {
         assert_eq!(url, "https://steamcommunity.com/tradeoffer/new?partner=39734272&token=token");
         
         let url = offer_referer_url(
+            &DEFAULT_COMMUNITY_URL,
             "new",
             SteamID::from(76561198000000000), 
             &None,
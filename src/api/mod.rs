@@ -7,11 +7,13 @@ pub mod request;
 mod builder;
 mod response_wrappers;
 mod helpers;
+mod trade_offer_api;
 
 use response::*;
 use response_wrappers::*;
 
 pub use builder::SteamTradeOfferAPIBuilder;
+pub use trade_offer_api::TradeOfferApi;
 
 use crate::SteamID;
 use crate::helpers::get_default_middleware;
@@ -21,11 +23,10 @@ use crate::enums::{Language, GetUserDetailsMethod};
 use crate::static_functions::get_inventory;
 use crate::serialize;
 use crate::helpers::{parses_response, generate_sessionid, get_sessionid_and_steamid_from_cookies};
-use crate::helpers::{COMMUNITY_HOSTNAME, WEB_API_HOSTNAME};
+use crate::helpers::join_url;
 use crate::error::{Error, ParameterError, MissingClassInfoError};
-use crate::classinfo_cache::{ClassInfoCache, helpers as classinfo_cache_helpers};
+use crate::classinfo_cache::{ClassInfoCache, ClassInfoStore, FileClassInfoStore, StoredClassInfo};
 use crate::request::{GetInventoryOptions, NewTradeOffer, NewTradeOfferItem, GetTradeHistoryOptions};
-use std::path::PathBuf;
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 use serde::{Deserialize, Serialize};
@@ -50,33 +51,34 @@ pub struct SteamTradeOfferAPI {
     sessionid: Arc<RwLock<Option<String>>>,
     /// The cache for setting and getting [`ClassInfo`] data.
     classinfo_cache: ClassInfoCache,
-    /// The directory to store [`ClassInfo`] data.
-    pub(crate) data_directory: PathBuf,
+    /// The storage backend for [`ClassInfo`] data.
+    classinfo_store: Arc<dyn ClassInfoStore>,
+    /// The base URL for Steam Community requests.
+    community_url: Url,
+    /// The base URL for Steam Web API requests.
+    web_api_url: Url,
 }
 
 impl SteamTradeOfferAPI {
-    /// Hostname for requests.
-    const HOSTNAME: &'static str = COMMUNITY_HOSTNAME;
-    /// Hostname for API requests.
-    const API_HOSTNAME: &'static str = WEB_API_HOSTNAME;
-    
     /// Builder for constructing a [`SteamTradeOfferAPI`].
     pub fn builder() -> SteamTradeOfferAPIBuilder {
         SteamTradeOfferAPIBuilder::new()
     }
     
     fn get_url(
+        &self,
         pathname: &str,
     ) -> String {
-        format!("https://{}{pathname}", Self::HOSTNAME)
+        join_url(&self.community_url, pathname)
     }
     
     fn get_api_url(
+        &self,
         interface: &str,
         method: &str,
         version: usize,
     ) -> String {
-        format!("https://{}/{interface}/{method}/v{version}", Self::API_HOSTNAME)
+        join_url(&self.web_api_url, &format!("/{interface}/{method}/v{version}"))
     }
     
     /// Sets cookies.
@@ -98,14 +100,10 @@ impl SteamTradeOfferAPI {
             cookies.push(format!("sessionid={sessionid}"));
             sessionid
         };
-        // Should not panic since the URL is hardcoded.
-        let url = format!("https://{}", Self::HOSTNAME).parse::<Url>()
-            .unwrap_or_else(|error| panic!("URL could not be parsed from {}: {}", Self::HOSTNAME, error));
-        
         *self.sessionid.write().unwrap() = Some(sessionid);
         
         for cookie_str in &cookies {
-            self.cookies.add_cookie_str(cookie_str, &url);
+            self.cookies.add_cookie_str(cookie_str, &self.community_url);
         }
     }
     
@@ -164,7 +162,7 @@ impl SteamTradeOfferAPI {
             };
             
             
-            helpers::offer_referer_url(&pathname, offer.partner, &offer.token.as_deref())?
+            helpers::offer_referer_url(&self.community_url, &pathname, offer.partner, &offer.token.as_deref())?
         };
         let params = {
             let json_tradeoffer = serde_json::to_string(&OfferForm {
@@ -197,7 +195,7 @@ impl SteamTradeOfferAPI {
                 tradeofferid_countered: &counter_tradeofferid,
             }
         };
-        let uri = Self::get_url("/tradeoffer/new/send");
+        let uri = self.get_url("/tradeoffer/new/send");
         let response = self.client.post(&uri)
             .header(REFERER, referer)
             .form(&params)
@@ -213,7 +211,7 @@ impl SteamTradeOfferAPI {
         &self,
         trade_id: &TradeId,
     ) -> Result<Vec<Asset>, Error> {
-        let uri = Self::get_url(&format!("/trade/{trade_id}/receipt"));
+        let uri = self.get_url(&format!("/trade/{trade_id}/receipt"));
         let response = self.client.get(&uri)
             .send()
             .await?;
@@ -269,7 +267,7 @@ impl SteamTradeOfferAPI {
             
             query
         };
-        let uri = Self::get_api_url("ISteamEconomy", "GetAssetClassInfo", 1);
+        let uri = self.get_api_url("ISteamEconomy", "GetAssetClassInfo", 1);
         let response = self.client.get(&uri)
             .query(&query)
             .send()
@@ -296,20 +294,23 @@ impl SteamTradeOfferAPI {
                 // should be quite efficient.
                 let pair = (
                     ((appid, classid, instanceid), Arc::new(classinfo)),
-                    ((classid, instanceid), classinfo_raw),
+                    ((appid, classid, instanceid), classinfo_raw),
                 );
                 
                 Some(pair)
             })
             .unzip();
-        // Save the classinfos to the filesystem.
-        // This spawns a tokio task which will save the classinfos to the filesystem in the 
-        // background so that this method does not need to await on it.
-        let _handle = classinfo_cache_helpers::save_classinfos(
-            appid,
-            classinfos_raw,
-            &self.data_directory,
-        );
+        // Save the classinfos to the store.
+        // This spawns a tokio task which will save the classinfos in the background so that this 
+        // method does not need to await on it.
+        let classinfo_store = Arc::clone(&self.classinfo_store);
+        let language = self.language;
+        let _handle = tokio::spawn(async move {
+            if let Err(error) = classinfo_store.save(language, &classinfos_raw).await {
+                // These are allowed to fail but we want a message of the error.
+                log::debug!("Error saving classinfos: {error}");
+            }
+        });
         
         // And return the classinfos.
         Ok(classinfos)
@@ -346,23 +347,32 @@ impl SteamTradeOfferAPI {
         let (
             mut map,
             misses,
-        ) = self.classinfo_cache.get_map(classes);
-        let mut needed = HashSet::from_iter(misses);
+        ) = self.classinfo_cache.get_map(self.language, classes);
+        let mut needed: HashSet<&ClassInfoClass> = HashSet::from_iter(misses);
         
         if !needed.is_empty() {
-            // Check filesystem for caches.
-            let results = classinfo_cache_helpers::load_classinfos(
-                &needed,
-                &self.data_directory,
-            ).await
-                .into_iter()
-                .flatten()
+            // Check the store for caches.
+            let classes = needed
+                .iter()
+                .map(|class| **class)
                 .collect::<Vec<_>>();
+            let results = self.classinfo_store.load(self.language, &classes).await
+                .unwrap_or_else(|error| {
+                    // Classinfos which cannot be loaded are requested from Steam instead.
+                    log::debug!("Error loading classinfos: {error}");
+                    HashMap::new()
+                });
             
             if !results.is_empty() {
                 let mut inserts = HashMap::with_capacity(results.len());
                 
-                for (class, classinfo) in results {
+                for (class, StoredClassInfo { classinfo, saved_at }) in results {
+                    // Expired classinfos and classinfos saved before their class was invalidated 
+                    // are fetched from Steam.
+                    if classinfo.is_cache_expired() || self.classinfo_cache.is_stale(&class, saved_at) {
+                        continue;
+                    }
+                    
                     let classinfo = Arc::new(classinfo);
                     
                     needed.remove(&class);
@@ -370,7 +380,7 @@ impl SteamTradeOfferAPI {
                 }
                 
                 // Insert the classinfos into the cache.
-                self.classinfo_cache.insert_map(inserts.clone());
+                self.classinfo_cache.insert_map(self.language, inserts.clone());
                 map.extend(inserts);
             }
         }
@@ -397,7 +407,7 @@ impl SteamTradeOfferAPI {
         
         if !cache_map.is_empty() {
             // Insert newly obtained classinfos into the cache for later use.
-            self.classinfo_cache.insert_map(cache_map);
+            self.classinfo_cache.insert_map(self.language, cache_map);
         }
         
         Ok(map)
@@ -431,7 +441,7 @@ impl SteamTradeOfferAPI {
             get_descriptions,
             historical_cutoff,
         } = options;
-        let uri = Self::get_api_url("IEconService", "GetTradeOffers", 1);
+        let uri = self.get_api_url("IEconService", "GetTradeOffers", 1);
         let key = self.api_key.as_ref()
             .ok_or(ParameterError::MissingApiKey)?;
         let mut cursor = None;
@@ -504,11 +514,11 @@ impl SteamTradeOfferAPI {
     }
     
     /// Combines trade offers with their descriptions using the cache and the Steam Web API. 
-    /// Ignores offers with missing descriptions.
+    /// Offers with missing descriptions are returned separately in the 2nd part of the tuple.
     pub async fn map_raw_trade_offers(
         &self,
         offers: Vec<response::RawTradeOffer>,
-    ) -> Result<Vec<TradeOffer>, Error> {
+    ) -> Result<(Vec<TradeOffer>, Vec<UnmappedTradeOffer>), Error> {
         let classes = offers
             .iter()
             .flat_map(|offer| {
@@ -522,35 +532,29 @@ impl SteamTradeOfferAPI {
             .into_iter()
             .collect::<Vec<_>>();
         let map = self.get_asset_classinfos(&classes).await?;
-        let offers = self.map_raw_trade_offers_with_descriptions(offers, map);
         
-        Ok(offers)
+        Ok(self.map_raw_trade_offers_with_descriptions(offers, map))
     }
     
-    /// Maps trade offer data with given descriptions. Ignores offers with missing descriptions.
+    /// Maps trade offer data with given descriptions. Offers with missing descriptions are 
+    /// returned separately in the 2nd part of the tuple.
     pub fn map_raw_trade_offers_with_descriptions(
         &self,
         offers: Vec<RawTradeOffer>,
         map: ClassInfoMap,
-    ) -> Vec<TradeOffer> {
-        offers
-            .into_iter()
-            // ignore offers where the classinfo cannot be obtained
-            // attempts to load the missing classinfos will continue
-            // but it will not cause the whole poll to fail
-            .filter_map(|offer| offer.try_combine_classinfos(&map).ok())
-            .collect()
+    ) -> (Vec<TradeOffer>, Vec<UnmappedTradeOffer>) {
+        helpers::map_raw_trade_offers_with_descriptions(offers, map)
     }
     
-    /// Gets trade offers.
+    /// Gets trade offers. Offers with missing descriptions are returned separately in the 2nd 
+    /// part of the tuple.
     pub async fn get_trade_offers(
         &self,
         options: &request::GetTradeOffersOptions,
-    ) -> Result<Vec<TradeOffer>, Error> {
+    ) -> Result<(Vec<TradeOffer>, Vec<UnmappedTradeOffer>), Error> {
         let (raw_offers, _descriptions) = self.get_raw_trade_offers(options).await?;
-        let offers = self.map_raw_trade_offers(raw_offers).await?;
         
-        Ok(offers)
+        self.map_raw_trade_offers(raw_offers).await
     }
     
     /// Gets a trade offer.
@@ -574,7 +578,7 @@ impl SteamTradeOfferAPI {
             response: Body,
         }
         
-        let uri = Self::get_api_url("IEconService", "GetTradeOffer", 1);
+        let uri = self.get_api_url("IEconService", "GetTradeOffer", 1);
         let key = self.api_key.as_ref()
             .ok_or(ParameterError::MissingApiKey)?;
         let response = self.client.get(&uri)
@@ -682,7 +686,7 @@ impl SteamTradeOfferAPI {
         // Convert the datetime to a UNIX timestamp.
         let start_after_time = start_after_time
             .map(|time| time.timestamp() as u32);
-        let uri = Self::get_api_url("IEconService", "GetTradeHistory", 1);
+        let uri = self.get_api_url("IEconService", "GetTradeHistory", 1);
         let key = self.api_key.as_ref()
             .ok_or(ParameterError::MissingApiKey)?;
         let response = self.client.get(&uri)
@@ -717,7 +721,7 @@ impl SteamTradeOfferAPI {
             let pathname = method.pathname();
             
             
-            helpers::offer_referer_url(&pathname, partner, &method.token())?
+            helpers::offer_referer_url(&self.community_url, &pathname, partner, &method.token())?
         };
         let response = self.client.get(&uri)
             .send()
@@ -749,7 +753,7 @@ impl SteamTradeOfferAPI {
         
         let sessionid = self.sessionid.read().unwrap().clone()
             .ok_or(Error::NotLoggedIn)?;
-        let referer = Self::get_url(&format!("/tradeoffer/{tradeofferid}"));
+        let referer = self.get_url(&format!("/tradeoffer/{tradeofferid}"));
         let params = AcceptOfferParams {
             sessionid,
             tradeofferid,
@@ -757,7 +761,7 @@ impl SteamTradeOfferAPI {
             serverid: 1,
             captcha: "",
         };
-        let uri = Self::get_url(&format!("/tradeoffer/{tradeofferid}/accept"));
+        let uri = self.get_url(&format!("/tradeoffer/{tradeofferid}/accept"));
         let response = self.client.post(&uri)
             .header(REFERER, referer)
             .form(&params)
@@ -786,8 +790,8 @@ impl SteamTradeOfferAPI {
         
         let sessionid = self.sessionid.read().unwrap().clone()
             .ok_or(Error::NotLoggedIn)?;
-        let referer = Self::get_url(&format!("/tradeoffer/{tradeofferid}"));
-        let uri = Self::get_url(&format!("/tradeoffer/{tradeofferid}/decline"));
+        let referer = self.get_url(&format!("/tradeoffer/{tradeofferid}"));
+        let uri = self.get_url(&format!("/tradeoffer/{tradeofferid}/decline"));
         let response = self.client.post(&uri)
             .header(REFERER, referer)
             .form(&DeclineOfferParams {
@@ -818,8 +822,8 @@ impl SteamTradeOfferAPI {
         
         let sessionid = self.sessionid.read().unwrap().clone()
             .ok_or(Error::NotLoggedIn)?;
-        let referer = Self::get_url(&format!("/tradeoffer/{tradeofferid}"));
-        let uri = Self::get_url(&format!("/tradeoffer/{tradeofferid}/cancel"));
+        let referer = self.get_url(&format!("/tradeoffer/{tradeofferid}"));
+        let uri = self.get_url(&format!("/tradeoffer/{tradeofferid}/cancel"));
         let response = self.client.post(&uri)
             .header(REFERER, referer)
             .form(&CancelOfferParams {
@@ -850,8 +854,8 @@ impl SteamTradeOfferAPI {
         let mut responses: Vec<GetInventoryOldResponse> = Vec::new();
         let mut start: Option<u64> = None;
         let sid = u64::from(steamid);
-        let uri = Self::get_url(&format!("/profiles/{sid}/inventory/json/{appid}/{contextid}"));
-        let referer = Self::get_url(&format!("/profiles/{sid}/inventory"));
+        let uri = self.get_url(&format!("/profiles/{sid}/inventory/json/{appid}/{contextid}"));
+        let referer = self.get_url(&format!("/profiles/{sid}/inventory"));
         
         loop {
             let response = self.client.get(&uri)
@@ -918,6 +922,7 @@ impl SteamTradeOfferAPI {
     ) -> Result<Vec<Asset>, Error> {
         get_inventory(&GetInventoryOptions {
             client: &self.client,
+            community_url: &self.community_url,
             steamid,
             appid,
             contextid,
@@ -944,8 +949,8 @@ impl SteamTradeOfferAPI {
         let mut responses: Vec<GetInventoryResponseIgnoreDescriptions> = Vec::new();
         let mut start_assetid: Option<u64> = None;
         let sid = u64::from(steamid);
-        let uri = Self::get_url(&format!("/inventory/{sid}/{appid}/{contextid}"));
-        let referer = Self::get_url(&format!("/profiles/{sid}/inventory"));
+        let uri = self.get_url(&format!("/inventory/{sid}/{appid}/{contextid}"));
+        let referer = self.get_url(&format!("/profiles/{sid}/inventory"));
         
         loop {
             let response = self.client.get(&uri)
@@ -1028,8 +1033,14 @@ impl From<SteamTradeOfferAPIBuilder> for SteamTradeOfferAPI {
             .unwrap_or_else(|| get_default_middleware(
                 Arc::clone(&cookies),
                 builder.user_agent,
+                builder.rate_limiter.map(|rate_limiter| rate_limiter
+                    .community_url(builder.community_url.clone())
+                    .web_api_url(builder.web_api_url.clone())),
+                builder.retry_policy,
             ));
         let classinfo_cache = builder.classinfo_cache.unwrap_or_default();
+        let classinfo_store = builder.classinfo_store
+            .unwrap_or_else(|| Arc::new(FileClassInfoStore::new(builder.data_directory)));
         
         Self {
             client,
@@ -1037,8 +1048,10 @@ impl From<SteamTradeOfferAPIBuilder> for SteamTradeOfferAPI {
             api_key: builder.api_key,
             language: builder.language,
             classinfo_cache,
-            data_directory: builder.data_directory,
+            classinfo_store,
             sessionid: Arc::new(std::sync::RwLock::new(None)),
+            community_url: builder.community_url,
+            web_api_url: builder.web_api_url,
         }
     }
 }
\ No newline at end of file
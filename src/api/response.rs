@@ -112,6 +112,7 @@ impl RawTradeOffer {
             time_created: self.time_created,
             escrow_end_date: self.escrow_end_date,
             confirmation_method: self.confirmation_method,
+            data: None,
         })
     }
     
@@ -119,6 +120,40 @@ impl RawTradeOffer {
     pub fn is_glitched(&self) -> bool {
         self.items_to_receive.is_empty() && self.items_to_give.is_empty()
     }
+    
+    /// The items in this offer which no longer exist in the inventory they belong to.
+    pub fn missing_items(&self) -> impl Iterator<Item = &RawAsset> {
+        self.items_to_give
+            .iter()
+            .chain(self.items_to_receive.iter())
+            .filter(|asset| asset.missing)
+    }
+    
+    /// Finds the first item in this offer which does not have a classinfo in the given map.
+    pub fn find_missing_classinfo(
+        &self,
+        map: &ClassInfoMap,
+    ) -> Option<MissingClassInfoError> {
+        self.items_to_give
+            .iter()
+            .chain(self.items_to_receive.iter())
+            .find(|asset| !map.contains_key(&(asset.appid, asset.classid, asset.instanceid)))
+            .map(|asset| MissingClassInfoError {
+                appid: asset.appid,
+                classid: asset.classid,
+                instanceid: asset.instanceid,
+            })
+    }
+}
+
+/// A trade offer which could not be combined with its descriptions because the classinfos for 
+/// some of its items could not be obtained.
+#[derive(Debug)]
+pub struct UnmappedTradeOffer {
+    /// The offer.
+    pub offer: RawTradeOffer,
+    /// The first missing classinfo for the offer.
+    pub error: MissingClassInfoError,
 }
 
 /// An asset belonging to a [`RawTrade`].
@@ -0,0 +1,372 @@
+use super::SteamTradeOfferAPI;
+use super::helpers;
+use super::request::GetTradeOffersOptions;
+use super::response::{RawTradeOffer, UnmappedTradeOffer};
+use crate::SteamID;
+use crate::enums::GetUserDetailsMethod;
+use crate::error::Error;
+use crate::request::{NewTradeOffer, GetTradeHistoryOptions};
+use crate::response::{SentOffer, AcceptedOffer, TradeOffer, Asset, UserDetails, Trades};
+use crate::types::{AppId, ContextId, TradeOfferId, TradeId, ClassInfoMap};
+use std::fmt;
+use async_trait::async_trait;
+
+/// The trade offer requests used by [`TradeOfferManager`][crate::TradeOfferManager] and its 
+/// poller.
+/// 
+/// [`SteamTradeOfferAPI`] is used by default. Implement this trait to substitute the requests, 
+/// e.g. with a stub returning fixed responses or failures when testing code built on the manager. 
+/// Set using 
+/// [`TradeOfferManagerBuilder::trade_offer_api`][crate::TradeOfferManagerBuilder::trade_offer_api].
+#[async_trait]
+pub trait TradeOfferApi: fmt::Debug + Send + Sync {
+    /// Sets cookies. Called with the cookies passed to the manager.
+    fn set_cookies(
+        &self,
+        cookies: &[String],
+    );
+    
+    /// Whether an API key is set. Polling cannot be started without an API key. Default is 
+    /// `true`.
+    fn has_api_key(&self) -> bool {
+        true
+    }
+    
+    /// Sends an offer. If `counter_tradeofferid` is set, the offer is sent as a counter offer to 
+    /// that offer.
+    async fn send_offer(
+        &self,
+        offer: &NewTradeOffer,
+        counter_tradeofferid: Option<TradeOfferId>,
+    ) -> Result<SentOffer, Error>;
+    
+    /// Accepts an offer.
+    async fn accept_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+        partner: SteamID,
+    ) -> Result<AcceptedOffer, Error>;
+    
+    /// Declines an offer.
+    async fn decline_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<TradeOfferId, Error>;
+    
+    /// Cancels an offer.
+    async fn cancel_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<TradeOfferId, Error>;
+    
+    /// Gets an offer without descriptions.
+    async fn get_trade_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<RawTradeOffer, Error>;
+    
+    /// Gets trade offers without mapping them to their descriptions. Descriptions are included 
+    /// when `get_descriptions` is set in `options`.
+    async fn get_raw_trade_offers(
+        &self,
+        options: &GetTradeOffersOptions,
+    ) -> Result<(Vec<RawTradeOffer>, Option<ClassInfoMap>), Error>;
+    
+    /// Maps trade offers to their descriptions. Offers with missing descriptions are returned 
+    /// separately in the 2nd part of the tuple.
+    async fn map_raw_trade_offers(
+        &self,
+        offers: Vec<RawTradeOffer>,
+    ) -> Result<(Vec<TradeOffer>, Vec<UnmappedTradeOffer>), Error>;
+    
+    /// Maps trade offers with the given descriptions. Offers with missing descriptions are 
+    /// returned separately in the 2nd part of the tuple.
+    fn map_raw_trade_offers_with_descriptions(
+        &self,
+        offers: Vec<RawTradeOffer>,
+        map: ClassInfoMap,
+    ) -> (Vec<TradeOffer>, Vec<UnmappedTradeOffer>) {
+        helpers::map_raw_trade_offers_with_descriptions(offers, map)
+    }
+    
+    /// Gets trade offers. Offers with missing descriptions are returned separately in the 2nd 
+    /// part of the tuple.
+    async fn get_trade_offers(
+        &self,
+        options: &GetTradeOffersOptions,
+    ) -> Result<(Vec<TradeOffer>, Vec<UnmappedTradeOffer>), Error>;
+    
+    /// Gets a user's inventory.
+    async fn get_inventory(
+        &self,
+        steamid: SteamID,
+        appid: AppId,
+        contextid: ContextId,
+        tradable_only: bool,
+    ) -> Result<Vec<Asset>, Error>;
+    
+    /// Gets escrow details for a user.
+    async fn get_user_details(
+        &self,
+        partner: SteamID,
+        method: GetUserDetailsMethod,
+    ) -> Result<UserDetails, Error>;
+    
+    /// Gets the trade receipt (new items) upon completion of a trade.
+    async fn get_receipt(
+        &self,
+        trade_id: &TradeId,
+    ) -> Result<Vec<Asset>, Error>;
+    
+    /// Gets trade history.
+    async fn get_trade_history(
+        &self,
+        options: &GetTradeHistoryOptions,
+    ) -> Result<Trades, Error>;
+}
+
+#[async_trait]
+impl TradeOfferApi for SteamTradeOfferAPI {
+    fn set_cookies(
+        &self,
+        cookies: &[String],
+    ) {
+        SteamTradeOfferAPI::set_cookies(self, cookies)
+    }
+    
+    fn has_api_key(&self) -> bool {
+        self.api_key.is_some()
+    }
+    
+    async fn send_offer(
+        &self,
+        offer: &NewTradeOffer,
+        counter_tradeofferid: Option<TradeOfferId>,
+    ) -> Result<SentOffer, Error> {
+        SteamTradeOfferAPI::send_offer(self, offer, counter_tradeofferid).await
+    }
+    
+    async fn accept_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+        partner: SteamID,
+    ) -> Result<AcceptedOffer, Error> {
+        SteamTradeOfferAPI::accept_offer(self, tradeofferid, partner).await
+    }
+    
+    async fn decline_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<TradeOfferId, Error> {
+        SteamTradeOfferAPI::decline_offer(self, tradeofferid).await
+    }
+    
+    async fn cancel_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<TradeOfferId, Error> {
+        SteamTradeOfferAPI::cancel_offer(self, tradeofferid).await
+    }
+    
+    async fn get_trade_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<RawTradeOffer, Error> {
+        SteamTradeOfferAPI::get_trade_offer(self, tradeofferid).await
+    }
+    
+    async fn get_raw_trade_offers(
+        &self,
+        options: &GetTradeOffersOptions,
+    ) -> Result<(Vec<RawTradeOffer>, Option<ClassInfoMap>), Error> {
+        SteamTradeOfferAPI::get_raw_trade_offers(self, options).await
+    }
+    
+    async fn map_raw_trade_offers(
+        &self,
+        offers: Vec<RawTradeOffer>,
+    ) -> Result<(Vec<TradeOffer>, Vec<UnmappedTradeOffer>), Error> {
+        SteamTradeOfferAPI::map_raw_trade_offers(self, offers).await
+    }
+    
+    async fn get_trade_offers(
+        &self,
+        options: &GetTradeOffersOptions,
+    ) -> Result<(Vec<TradeOffer>, Vec<UnmappedTradeOffer>), Error> {
+        SteamTradeOfferAPI::get_trade_offers(self, options).await
+    }
+    
+    async fn get_inventory(
+        &self,
+        steamid: SteamID,
+        appid: AppId,
+        contextid: ContextId,
+        tradable_only: bool,
+    ) -> Result<Vec<Asset>, Error> {
+        SteamTradeOfferAPI::get_inventory(self, steamid, appid, contextid, tradable_only).await
+    }
+    
+    async fn get_user_details(
+        &self,
+        partner: SteamID,
+        method: GetUserDetailsMethod,
+    ) -> Result<UserDetails, Error> {
+        SteamTradeOfferAPI::get_user_details(self, partner, method).await
+    }
+    
+    async fn get_receipt(
+        &self,
+        trade_id: &TradeId,
+    ) -> Result<Vec<Asset>, Error> {
+        SteamTradeOfferAPI::get_receipt(self, trade_id).await
+    }
+    
+    async fn get_trade_history(
+        &self,
+        options: &GetTradeHistoryOptions,
+    ) -> Result<Trades, Error> {
+        SteamTradeOfferAPI::get_trade_history(self, options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TradeOfferManager;
+    use crate::RetryPolicy;
+    use crate::api::response::RawAsset;
+    use crate::enums::{TradeOfferState, ConfirmationMethod};
+    use crate::error::TradeOfferError;
+    use crate::request::NewTradeOfferItem;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    
+    /// Fails sending offers with a timeout after recording them as sent.
+    #[derive(Debug, Default)]
+    struct TimeoutApi {
+        sent: Arc<Mutex<Vec<RawTradeOffer>>>,
+    }
+    
+    #[async_trait]
+    impl TradeOfferApi for TimeoutApi {
+        fn set_cookies(&self, _cookies: &[String]) {}
+        
+        async fn send_offer(
+            &self,
+            offer: &NewTradeOffer,
+            _counter_tradeofferid: Option<TradeOfferId>,
+        ) -> Result<SentOffer, Error> {
+            let mut sent = self.sent.lock().unwrap();
+            let now = crate::time::get_server_time_now();
+            let tradeofferid = sent.len() as u64 + 1;
+            let items_to_give = offer.items_to_give
+                .iter()
+                .map(|item| RawAsset {
+                    appid: item.appid,
+                    contextid: item.contextid,
+                    assetid: item.assetid,
+                    amount: item.amount,
+                    missing: false,
+                    classid: 1,
+                    instanceid: None,
+                })
+                .collect();
+            
+            sent.push(RawTradeOffer {
+                tradeofferid,
+                tradeid: None,
+                accountid_other: offer.partner.account_id(),
+                message: None,
+                items_to_receive: Vec::new(),
+                items_to_give,
+                is_our_offer: true,
+                from_real_time_trade: false,
+                expiration_time: now,
+                time_created: now,
+                time_updated: now,
+                trade_offer_state: TradeOfferState::Active,
+                escrow_end_date: None,
+                confirmation_method: ConfirmationMethod::None,
+            });
+            Err(Error::TradeOffer(TradeOfferError::Timeout))
+        }
+        
+        async fn accept_offer(&self, _: TradeOfferId, _: SteamID) -> Result<AcceptedOffer, Error> {
+            Err(Error::MalformedResponse("Not supported."))
+        }
+        
+        async fn decline_offer(&self, _: TradeOfferId) -> Result<TradeOfferId, Error> {
+            Err(Error::MalformedResponse("Not supported."))
+        }
+        
+        async fn cancel_offer(&self, _: TradeOfferId) -> Result<TradeOfferId, Error> {
+            Err(Error::MalformedResponse("Not supported."))
+        }
+        
+        async fn get_trade_offer(&self, _: TradeOfferId) -> Result<RawTradeOffer, Error> {
+            Err(Error::MalformedResponse("Not supported."))
+        }
+        
+        async fn get_raw_trade_offers(
+            &self,
+            _options: &GetTradeOffersOptions,
+        ) -> Result<(Vec<RawTradeOffer>, Option<ClassInfoMap>), Error> {
+            Ok((self.sent.lock().unwrap().clone(), None))
+        }
+        
+        async fn map_raw_trade_offers(
+            &self,
+            _offers: Vec<RawTradeOffer>,
+        ) -> Result<(Vec<TradeOffer>, Vec<UnmappedTradeOffer>), Error> {
+            Err(Error::MalformedResponse("Not supported."))
+        }
+        
+        async fn get_trade_offers(
+            &self,
+            _options: &GetTradeOffersOptions,
+        ) -> Result<(Vec<TradeOffer>, Vec<UnmappedTradeOffer>), Error> {
+            Err(Error::MalformedResponse("Not supported."))
+        }
+        
+        async fn get_inventory(&self, _: SteamID, _: AppId, _: ContextId, _: bool) -> Result<Vec<Asset>, Error> {
+            Err(Error::MalformedResponse("Not supported."))
+        }
+        
+        async fn get_user_details(&self, _: SteamID, _: GetUserDetailsMethod) -> Result<UserDetails, Error> {
+            Err(Error::MalformedResponse("Not supported."))
+        }
+        
+        async fn get_receipt(&self, _: &TradeId) -> Result<Vec<Asset>, Error> {
+            Err(Error::MalformedResponse("Not supported."))
+        }
+        
+        async fn get_trade_history(&self, _: &GetTradeHistoryOptions) -> Result<Trades, Error> {
+            Err(Error::MalformedResponse("Not supported."))
+        }
+    }
+    
+    #[tokio::test]
+    async fn manager_uses_injected_api() {
+        let api = TimeoutApi::default();
+        let sent = Arc::clone(&api.sent);
+        let manager = TradeOfferManager::builder()
+            .trade_offer_api(api)
+            .retry_policy(RetryPolicy {
+                delay: Duration::from_millis(1),
+                ..RetryPolicy::default()
+            })
+            .build();
+        let offer = NewTradeOffer::builder(SteamID::from(76561198080179568))
+            .items_to_give(vec![NewTradeOfferItem {
+                appid: 440,
+                contextid: 2,
+                assetid: 1,
+                amount: 1,
+            }])
+            .build();
+        let sent_offer = manager.send_offer(&offer).await.unwrap();
+        
+        assert_eq!(sent_offer.tradeofferid, 1);
+        assert_eq!(sent.lock().unwrap().len(), 1);
+    }
+}
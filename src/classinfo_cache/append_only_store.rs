@@ -0,0 +1,414 @@
+use super::{ClassInfoStore, StoredClassInfo};
+use crate::response::ClassInfo;
+use crate::enums::Language;
+use crate::error::AnyhowError;
+use crate::time;
+use crate::types::{AppId, ClassId, InstanceId, ClassInfoClass, ServerTime};
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_trait::async_trait;
+use futures::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader, BufWriter};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use tokio::sync::Mutex;
+
+/// A line in the file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Record<'a> {
+    language: Language,
+    appid: AppId,
+    classid: ClassId,
+    instanceid: InstanceId,
+    #[serde(borrow)]
+    classinfo: &'a RawValue,
+    /// Lines saved by older versions do not have this.
+    #[serde(default)]
+    saved_at: Option<ServerTime>,
+}
+
+/// Maps each stored class to the position of its latest line in the file.
+#[derive(Debug, Default)]
+struct Index {
+    records: HashMap<(Language, ClassInfoClass), (u64, usize)>,
+    /// How far into the file has been indexed.
+    len: u64,
+}
+
+/// Stores every classinfo in a single file of JSON lines, with an in-memory index of where each 
+/// classinfo is in the file. Classinfos are only ever appended; a class saved more than once uses 
+/// its latest line.
+/// 
+/// The file can be shared between processes. Lines appended by other processes are indexed before 
+/// each load. Saves are not locked between processes, so lines from concurrent saves may be 
+/// interleaved; lines which cannot be parsed are skipped and their classinfos are fetched from 
+/// Steam again.
+/// 
+/// The file grows with each save and is read in full the first time it is indexed. Lines which 
+/// were superseded by later lines can be removed using 
+/// [`compact`][AppendOnlyClassInfoStore::compact].
+/// 
+/// # Examples
+/// ```
+/// use steam_tradeoffer_manager::TradeOfferManager;
+/// use steam_tradeoffer_manager::classinfo_store::AppendOnlyClassInfoStore;
+/// 
+/// let builder = TradeOfferManager::builder()
+///     .classinfo_store(AppendOnlyClassInfoStore::new("classinfos.jsonl"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct AppendOnlyClassInfoStore {
+    filepath: PathBuf,
+    index: Arc<Mutex<Index>>,
+}
+
+impl AppendOnlyClassInfoStore {
+    /// Creates a new [`AppendOnlyClassInfoStore`] storing classinfos in the file at `filepath`. 
+    /// The file is created when classinfos are first saved.
+    pub fn new<T>(filepath: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        Self {
+            filepath: filepath.into(),
+            index: Arc::new(Mutex::new(Index::default())),
+        }
+    }
+    
+    /// Rewrites the file keeping only the latest line for each class. Returns the number of bytes 
+    /// removed from the file.
+    /// 
+    /// The file is replaced once the rewritten file is complete. Lines appended by other 
+    /// processes while compacting are lost, so this should only be called while no other process 
+    /// is saving to the file.
+    pub async fn compact(&self) -> Result<u64, AnyhowError> {
+        let mut index = self.index.lock().await;
+        
+        self.refresh_index(&mut index).await?;
+        
+        if index.len == 0 {
+            return Ok(0);
+        }
+        
+        let mut positions = index.records
+            .values()
+            .copied()
+            .collect::<Vec<_>>();
+        
+        // Reads the lines in the order they are in the file.
+        positions.sort_unstable();
+        
+        let mut temp_filepath = self.filepath.clone().into_os_string();
+        
+        temp_filepath.push(".tmp");
+        
+        let temp_filepath = PathBuf::from(temp_filepath);
+        let mut file = async_fs::File::open(&self.filepath).await?;
+        let mut writer = BufWriter::new(async_fs::File::create(&temp_filepath).await?);
+        let mut line = Vec::new();
+        
+        for (offset, len) in positions {
+            line.resize(len, 0);
+            file.seek(SeekFrom::Start(offset)).await?;
+            file.read_exact(&mut line).await?;
+            writer.write_all(&line).await?;
+            writer.write_all(b"\n").await?;
+        }
+        
+        writer.flush().await?;
+        writer.into_inner().sync_all().await?;
+        drop(file);
+        async_fs::rename(&temp_filepath, &self.filepath).await?;
+        
+        let previous_len = index.len;
+        
+        *index = Index::default();
+        self.refresh_index(&mut index).await?;
+        Ok(previous_len.saturating_sub(index.len))
+    }
+    
+    /// Reads the classinfos for `classes` using their positions in `index`. Returns `None` if a 
+    /// line is not at its indexed position.
+    async fn read_classinfos(
+        &self,
+        index: &Index,
+        language: Language,
+        classes: &[ClassInfoClass],
+    ) -> Result<Option<HashMap<ClassInfoClass, StoredClassInfo>>, AnyhowError> {
+        let positions = classes
+            .iter()
+            .filter_map(|class| {
+                index.records.get(&(language, *class)).map(|position| (*class, *position))
+            })
+            .collect::<Vec<_>>();
+        let mut classinfos = HashMap::with_capacity(positions.len());
+        
+        if positions.is_empty() {
+            return Ok(Some(classinfos));
+        }
+        
+        let mut file = async_fs::File::open(&self.filepath).await?;
+        
+        for (class, (offset, len)) in positions {
+            let mut line = vec![0; len];
+            
+            file.seek(SeekFrom::Start(offset)).await?;
+            
+            if file.read_exact(&mut line).await.is_err() {
+                return Ok(None);
+            }
+            
+            let record = match serde_json::from_slice::<Record>(&line) {
+                Ok(record) if
+                    record.language == language &&
+                    (record.appid, record.classid, record.instanceid) == class
+                => record,
+                _ => return Ok(None),
+            };
+            
+            match serde_json::from_str::<ClassInfo>(record.classinfo.get()) {
+                Ok(classinfo) => {
+                    classinfos.insert(class, StoredClassInfo {
+                        classinfo,
+                        saved_at: record.saved_at,
+                    });
+                },
+                Err(error) => {
+                    log::debug!("Error parsing stored classinfo: {error}");
+                },
+            }
+        }
+        
+        Ok(Some(classinfos))
+    }
+    
+    /// Indexes lines appended to the file since it was last indexed.
+    async fn refresh_index(
+        &self,
+        index: &mut Index,
+    ) -> Result<(), AnyhowError> {
+        let mut file = match async_fs::File::open(&self.filepath).await {
+            Ok(file) => file,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                *index = Index::default();
+                return Ok(());
+            },
+            Err(error) => return Err(error.into()),
+        };
+        let file_len = file.metadata().await?.len();
+        
+        if file_len < index.len {
+            // The file was replaced or truncated.
+            *index = Index::default();
+        }
+        
+        if file_len == index.len {
+            return Ok(());
+        }
+        
+        file.seek(SeekFrom::Start(index.len)).await?;
+        
+        // Lines are read one at a time so that the whole file is not held in memory.
+        let mut reader = BufReader::new(file);
+        let mut line = Vec::new();
+        
+        loop {
+            line.clear();
+            
+            let read_len = reader.read_until(b'\n', &mut line).await?;
+            
+            // An incomplete line at the end is left to be indexed once it is complete.
+            if line.pop() != Some(b'\n') {
+                break;
+            }
+            
+            if let Ok(record) = serde_json::from_slice::<Record>(&line) {
+                let class = (record.appid, record.classid, record.instanceid);
+                let position = (index.len, line.len());
+                
+                index.records.insert((record.language, class), position);
+            } else if !line.is_empty() {
+                log::debug!("Skipping invalid classinfo line in {}", self.filepath.display());
+            }
+            
+            index.len += read_len as u64;
+        }
+        
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ClassInfoStore for AppendOnlyClassInfoStore {
+    async fn load(
+        &self,
+        language: Language,
+        classes: &[ClassInfoClass],
+    ) -> Result<HashMap<ClassInfoClass, StoredClassInfo>, AnyhowError> {
+        let mut index = self.index.lock().await;
+        
+        self.refresh_index(&mut index).await?;
+        
+        if let Some(classinfos) = self.read_classinfos(&index, language, classes).await? {
+            return Ok(classinfos);
+        }
+        
+        // The file was replaced since it was indexed, e.g. by compacting it in another process.
+        *index = Index::default();
+        self.refresh_index(&mut index).await?;
+        
+        Ok(self.read_classinfos(&index, language, classes).await?.unwrap_or_default())
+    }
+    
+    async fn save(
+        &self,
+        language: Language,
+        classinfos: &[(ClassInfoClass, Box<RawValue>)],
+    ) -> Result<(), AnyhowError> {
+        if classinfos.is_empty() {
+            return Ok(());
+        }
+        
+        // Starts with a newline so that an incomplete line left by a failed write does not 
+        // corrupt the first record.
+        let mut buffer = vec![b'\n'];
+        let saved_at = Some(time::get_server_time_now());
+        
+        for ((appid, classid, instanceid), classinfo) in classinfos {
+            let record = Record {
+                language,
+                appid: *appid,
+                classid: *classid,
+                instanceid: *instanceid,
+                classinfo,
+                saved_at,
+            };
+            
+            serde_json::to_writer(&mut buffer, &record)?;
+            buffer.push(b'\n');
+        }
+        
+        let mut index = self.index.lock().await;
+        let mut file = async_fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.filepath)
+            .await?;
+        
+        file.write_all(&buffer).await?;
+        file.flush().await?;
+        drop(file);
+        self.refresh_index(&mut index).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::fake_classinfo;
+    
+    fn temp_filepath(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join("steam-tradeoffer-manager-classinfo-tests");
+        // Unique to the process so that concurrent test runs do not share files.
+        let filepath = directory.join(format!("{name}-{}.jsonl", std::process::id()));
+        
+        std::fs::create_dir_all(&directory).unwrap();
+        let _ = std::fs::remove_file(&filepath);
+        filepath
+    }
+    
+    fn raw_classinfo(classid: ClassId, name: &str) -> Box<RawValue> {
+        serde_json::value::to_raw_value(&fake_classinfo(classid, name)).unwrap()
+    }
+    
+    #[tokio::test]
+    async fn loads_classinfos_saved_by_other_stores() {
+        let filepath = temp_filepath("loads_classinfos_saved_by_other_stores");
+        let store = AppendOnlyClassInfoStore::new(&filepath);
+        let other_store = AppendOnlyClassInfoStore::new(&filepath);
+        let key = (440, 101, None);
+        let hat = (440, 102, Some(5));
+        
+        assert!(other_store.load(Language::English, &[key]).await.unwrap().is_empty());
+        
+        store.save(Language::English, &[(key, raw_classinfo(101, "Key"))]).await.unwrap();
+        store.save(Language::German, &[(hat, raw_classinfo(102, "Hut"))]).await.unwrap();
+        
+        let loaded = other_store.load(Language::English, &[key, hat]).await.unwrap();
+        
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[&key].classinfo.name, "Key");
+        
+        let loaded = other_store.load(Language::German, &[key, hat]).await.unwrap();
+        
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[&hat].classinfo.name, "Hut");
+    }
+    
+    #[tokio::test]
+    async fn uses_latest_line_and_skips_incomplete_lines() {
+        let filepath = temp_filepath("uses_latest_line_and_skips_incomplete_lines");
+        let store = AppendOnlyClassInfoStore::new(&filepath);
+        let key = (440, 101, None);
+        
+        store.save(Language::English, &[(key, raw_classinfo(101, "Old Key"))]).await.unwrap();
+        store.save(Language::English, &[(key, raw_classinfo(101, "Key"))]).await.unwrap();
+        
+        // Simulates a write which failed partway through.
+        let mut contents = std::fs::read(&filepath).unwrap();
+        
+        contents.extend_from_slice(b"{\"language\":\"English\",\"appid\":440,");
+        std::fs::write(&filepath, contents).unwrap();
+        
+        let loaded = store.load(Language::English, &[key]).await.unwrap();
+        
+        assert_eq!(loaded[&key].classinfo.name, "Key");
+        
+        let hat = (440, 102, None);
+        
+        store.save(Language::English, &[(hat, raw_classinfo(102, "Hat"))]).await.unwrap();
+        
+        let loaded = AppendOnlyClassInfoStore::new(&filepath)
+            .load(Language::English, &[key, hat])
+            .await
+            .unwrap();
+        
+        assert_eq!(loaded[&key].classinfo.name, "Key");
+        assert_eq!(loaded[&hat].classinfo.name, "Hat");
+    }
+    
+    #[tokio::test]
+    async fn compacts_superseded_lines() {
+        let filepath = temp_filepath("compacts_superseded_lines");
+        let store = AppendOnlyClassInfoStore::new(&filepath);
+        let other_store = AppendOnlyClassInfoStore::new(&filepath);
+        let key = (440, 101, None);
+        let hat = (440, 102, None);
+        
+        store.save(Language::English, &[(key, raw_classinfo(101, "Old Key"))]).await.unwrap();
+        store.save(Language::English, &[(hat, raw_classinfo(102, "Hat"))]).await.unwrap();
+        store.save(Language::English, &[(key, raw_classinfo(101, "Key"))]).await.unwrap();
+        store.save(Language::German, &[(key, raw_classinfo(101, "Schlüssel"))]).await.unwrap();
+        
+        assert_eq!(other_store.load(Language::English, &[key]).await.unwrap()[&key].classinfo.name, "Key");
+        
+        let len = std::fs::metadata(&filepath).unwrap().len();
+        let removed = store.compact().await.unwrap();
+        
+        assert!(removed > 0);
+        assert_eq!(std::fs::metadata(&filepath).unwrap().len(), len - removed);
+        assert_eq!(std::fs::read_to_string(&filepath).unwrap().lines().count(), 3);
+        
+        // Stores which indexed the file before it was compacted index it again.
+        let loaded = other_store.load(Language::English, &[key, hat]).await.unwrap();
+        
+        assert_eq!(loaded[&key].classinfo.name, "Key");
+        assert_eq!(loaded[&hat].classinfo.name, "Hat");
+        
+        let loaded = store.load(Language::German, &[key]).await.unwrap();
+        
+        assert_eq!(loaded[&key].classinfo.name, "Schlüssel");
+    }
+}
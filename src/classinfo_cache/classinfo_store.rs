@@ -0,0 +1,261 @@
+use crate::response::ClassInfo;
+use crate::enums::Language;
+use crate::error::{AnyhowError, FileError};
+use crate::types::{ClassInfoClass, ServerTime};
+use crate::helpers::write_file_atomic;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_trait::async_trait;
+use serde_json::value::RawValue;
+use futures::future::join_all;
+
+/// Storage backend for [`ClassInfo`] data. Classinfos missing from the 
+/// [`ClassInfoCache`][crate::ClassInfoCache] are loaded from the store before being requested 
+/// from Steam, and classinfos obtained from Steam are saved to the store in the background.
+/// 
+/// By default [`FileClassInfoStore`] is used, which stores each classinfo as a JSON file in the 
+/// data directory. [`AppendOnlyClassInfoStore`][super::AppendOnlyClassInfoStore] stores every 
+/// classinfo in a single file, which is better suited to large numbers of classinfos. Set using 
+/// [`SteamTradeOfferAPIBuilder::classinfo_store`][crate::api::SteamTradeOfferAPIBuilder::classinfo_store] 
+/// or 
+/// [`TradeOfferManagerBuilder::classinfo_store`][crate::TradeOfferManagerBuilder::classinfo_store]. 
+/// Implemented for `Arc<T>` so that one store can be shared between builders.
+#[async_trait]
+pub trait ClassInfoStore: fmt::Debug + Send + Sync {
+    /// Loads the classinfos in `language` for `classes`. Classes which are not stored are omitted 
+    /// from the returned map.
+    async fn load(
+        &self,
+        language: Language,
+        classes: &[ClassInfoClass],
+    ) -> Result<HashMap<ClassInfoClass, StoredClassInfo>, AnyhowError>;
+    
+    /// Saves classinfos in `language`. Each classinfo is the raw JSON value returned by Steam, 
+    /// which has already been checked to deserialize into a [`ClassInfo`].
+    async fn save(
+        &self,
+        language: Language,
+        classinfos: &[(ClassInfoClass, Box<RawValue>)],
+    ) -> Result<(), AnyhowError>;
+}
+
+#[async_trait]
+impl<T> ClassInfoStore for Arc<T>
+where
+    T: ClassInfoStore + ?Sized,
+{
+    async fn load(
+        &self,
+        language: Language,
+        classes: &[ClassInfoClass],
+    ) -> Result<HashMap<ClassInfoClass, StoredClassInfo>, AnyhowError> {
+        (**self).load(language, classes).await
+    }
+    
+    async fn save(
+        &self,
+        language: Language,
+        classinfos: &[(ClassInfoClass, Box<RawValue>)],
+    ) -> Result<(), AnyhowError> {
+        (**self).save(language, classinfos).await
+    }
+}
+
+/// A classinfo loaded from a [`ClassInfoStore`].
+#[derive(Debug, Clone)]
+pub struct StoredClassInfo {
+    /// The classinfo.
+    pub classinfo: ClassInfo,
+    /// When the classinfo was saved, if known. Classinfos saved before their class was 
+    /// invalidated using [`ClassInfoCache`][crate::ClassInfoCache] are fetched from Steam again. 
+    /// Classinfos without a save time are treated as saved before any invalidation.
+    pub saved_at: Option<ServerTime>,
+}
+
+/// Stores each classinfo as a `<appid>_<classid>_<instanceid>_<language>.json` file in a 
+/// directory. Files saved by older versions without the language in their name are migrated to 
+/// English when loaded.
+#[derive(Debug, Clone)]
+pub struct FileClassInfoStore {
+    directory: PathBuf,
+}
+
+impl FileClassInfoStore {
+    /// Creates a new [`FileClassInfoStore`] storing files in `directory`.
+    pub fn new<T>(directory: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        Self {
+            directory: directory.into(),
+        }
+    }
+    
+    async fn load_classinfo(
+        &self,
+        class: ClassInfoClass,
+        language: Language,
+    ) -> Result<StoredClassInfo, FileError> {
+        let filepath = get_classinfo_file_path(class, language, &self.directory);
+        let data = match async_fs::read_to_string(&filepath).await {
+            Ok(data) => data,
+            // Files saved before the language was included in file names are in English.
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound && language == Language::English => {
+                let legacy_filepath = get_legacy_classinfo_file_path(class, &self.directory);
+                
+                async_fs::rename(&legacy_filepath, &filepath).await?;
+                async_fs::read_to_string(&filepath).await?
+            },
+            Err(error) => return Err(error.into()),
+        };
+        
+        match serde_json::from_str::<ClassInfo>(&data) {
+            Ok(classinfo) => {
+                let saved_at = async_fs::metadata(&filepath).await
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .map(ServerTime::from);
+                
+                Ok(StoredClassInfo {
+                    classinfo,
+                    saved_at,
+                })
+            },
+            Err(error) => {
+                // Remove the file...
+                let _ = async_fs::remove_file(&filepath).await;
+                
+                Err(FileError::Parse(error))
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl ClassInfoStore for FileClassInfoStore {
+    async fn load(
+        &self,
+        language: Language,
+        classes: &[ClassInfoClass],
+    ) -> Result<HashMap<ClassInfoClass, StoredClassInfo>, AnyhowError> {
+        let tasks = classes
+            .iter()
+            .map(|class| async move {
+                // Missing files are expected so errors are ignored.
+                let classinfo = self.load_classinfo(*class, language).await.ok()?;
+                
+                Some((*class, classinfo))
+            })
+            .collect::<Vec<_>>();
+        
+        Ok(join_all(tasks).await.into_iter().flatten().collect())
+    }
+    
+    async fn save(
+        &self,
+        language: Language,
+        classinfos: &[(ClassInfoClass, Box<RawValue>)],
+    ) -> Result<(), AnyhowError> {
+        let tasks = classinfos
+            .iter()
+            .map(|(class, classinfo)| {
+                let filepath = get_classinfo_file_path(*class, language, &self.directory);
+                
+                write_file_atomic(filepath, classinfo.get().as_bytes())
+            })
+            .collect::<Vec<_>>();
+        
+        for result in join_all(tasks).await {
+            if let Err(error) = result {
+                // These are allowed to fail but we want a message of the error.
+                log::debug!("Error saving classinfo: {error}");
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+fn get_classinfo_file_path(
+    class: ClassInfoClass,
+    language: Language,
+    data_directory: &Path, 
+) -> PathBuf {
+    let (appid, classid, instanceid) = class;
+    let filename = format!(
+        "{}_{}_{}_{}.json",
+        appid,
+        classid,
+        instanceid.unwrap_or(0),
+        language.api_language_code(),
+    );
+    
+    data_directory.join(filename)
+}
+
+/// Gets the path classinfos were saved to before the language was included in file names.
+fn get_legacy_classinfo_file_path(
+    class: ClassInfoClass,
+    data_directory: &Path, 
+) -> PathBuf {
+    let (appid, classid, instanceid) = class;
+    let filename = format!("{}_{}_{}.json", appid, classid, instanceid.unwrap_or(0));
+    
+    data_directory.join(filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn temp_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join("steam-tradeoffer-manager-classinfo-tests")
+            // Unique to the process so that concurrent test runs do not share files.
+            .join(format!("{name}-{}", std::process::id()));
+        
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+    
+    #[test]
+    fn includes_language_in_file_path() {
+        let directory = Path::new("classinfos");
+        
+        assert_eq!(
+            get_classinfo_file_path((440, 101, None), Language::English, directory),
+            directory.join("440_101_0_english.json"),
+        );
+        assert_eq!(
+            get_classinfo_file_path((440, 101, Some(5)), Language::German, directory),
+            directory.join("440_101_5_german.json"),
+        );
+    }
+    
+    #[tokio::test]
+    async fn migrates_legacy_files_to_english() {
+        let directory = temp_directory("migrates_legacy_files_to_english");
+        let store = FileClassInfoStore::new(&directory);
+        let class = (440, 101, None);
+        let legacy_filepath = get_legacy_classinfo_file_path(class, &directory);
+        let classinfo = crate::test_util::fake_classinfo(101, "Key");
+        
+        std::fs::write(&legacy_filepath, serde_json::to_string(&classinfo).unwrap()).unwrap();
+        
+        // Legacy files are not used for other languages.
+        assert!(store.load(Language::German, &[class]).await.unwrap().is_empty());
+        assert!(legacy_filepath.exists());
+        
+        let loaded = store.load(Language::English, &[class]).await.unwrap();
+        
+        assert_eq!(loaded[&class].classinfo.classid, 101);
+        assert!(loaded[&class].saved_at.is_some());
+        assert!(!legacy_filepath.exists());
+        assert!(get_classinfo_file_path(class, Language::English, &directory).exists());
+        
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
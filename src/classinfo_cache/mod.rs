@@ -1,12 +1,18 @@
-pub(crate) mod helpers;
+mod classinfo_store;
+mod append_only_store;
+
+pub use classinfo_store::{ClassInfoStore, FileClassInfoStore, StoredClassInfo};
+pub use append_only_store::AppendOnlyClassInfoStore;
 
 use crate::response::ClassInfo;
-use crate::types::ClassInfoClass;
+use crate::enums::Language;
+use crate::time;
+use crate::types::{AppId, ClassInfoClass, ServerTime};
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
 use lfu_cache::LfuCache;
 
-type LfuClassInfoMap = LfuCache<ClassInfoClass, Arc<ClassInfo>>;
+type LfuClassInfoMap = LfuCache<(Language, ClassInfoClass), Arc<ClassInfo>>;
 
 const DEFAULT_CACHE_SIZE: usize = 1000;
 
@@ -17,7 +23,13 @@ const DEFAULT_CACHE_SIZE: usize = 1000;
 /// 
 /// Internally the cache is wrapped in an `Arc<Mutex<T>>`. This allows you to clone the 
 /// [`ClassInfoCache`] and share it between multiple instances of 
-/// [`TradeOfferManager`][crate::TradeOfferManager] to reduce file reads and memory usage.
+/// [`TradeOfferManager`][crate::TradeOfferManager] to reduce file reads and memory usage. 
+/// Classinfos are stored by [`Language`], so instances using different languages can share a 
+/// cache without serving each other's descriptions.
+/// 
+/// Classinfos past their [`cache_expiration`][ClassInfo::cache_expiration] are treated as missing 
+/// and fetched again. Use [`ClassInfoCache::invalidate`] or [`ClassInfoCache::invalidate_app`] to 
+/// fetch classinfos again before they expire.
 /// 
 /// # Examples
 /// ```
@@ -29,7 +41,41 @@ const DEFAULT_CACHE_SIZE: usize = 1000;
 /// ```
 #[derive(Debug, Clone)]
 pub struct ClassInfoCache {
-    inner: Arc<Mutex<LfuClassInfoMap>>,
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    classinfos: LfuClassInfoMap,
+    /// When each class was last invalidated.
+    invalidated: HashMap<ClassInfoClass, ServerTime>,
+    /// When each app was last invalidated.
+    invalidated_apps: HashMap<AppId, ServerTime>,
+}
+
+impl Inner {
+    /// Gets when `class` was last invalidated, either by itself or with its app.
+    fn invalidated_at(&self, class: &ClassInfoClass) -> Option<ServerTime> {
+        let (appid, _classid, _instanceid) = class;
+        
+        self.invalidated.get(class).max(self.invalidated_apps.get(appid)).copied()
+    }
+    
+    /// Removes classinfos in every language matching `predicate`.
+    fn remove_where<F>(&mut self, predicate: F)
+    where
+        F: Fn(&ClassInfoClass) -> bool,
+    {
+        let keys = self.classinfos
+            .keys()
+            .filter(|(_language, class)| predicate(class))
+            .copied()
+            .collect::<Vec<_>>();
+        
+        for key in keys {
+            self.classinfos.remove(&key);
+        }
+    }
 }
 
 impl Default for ClassInfoCache {
@@ -43,17 +89,24 @@ impl ClassInfoCache {
     pub fn with_capacity(
         capacity: usize,
     ) -> Self {
-        let map = LfuClassInfoMap::with_capacity(capacity);
+        let classinfos = LfuClassInfoMap::with_capacity(capacity);
         
         Self {
-            inner: Arc::new(Mutex::new(map)),
+            inner: Arc::new(Mutex::new(Inner {
+                classinfos,
+                invalidated: HashMap::new(),
+                invalidated_apps: HashMap::new(),
+            })),
         }
     }
     
-    /// Gets a map of [`ClassInfo`] wrapped in an [`Arc`] from the cache. The second element of 
-    /// the returned tuple is a [`Vec`] of classes that were not found in the cache.
+    /// Gets a map of [`ClassInfo`] wrapped in an [`Arc`] in `language` from the cache. The second 
+    /// element of the returned tuple is a [`Vec`] of classes that were not found in the cache. 
+    /// Classinfos past their [`cache_expiration`][ClassInfo::cache_expiration] are removed and 
+    /// included in the missing classes.
     pub fn get_map<'a>(
         &self,
+        language: Language,
         classes: &'a [ClassInfoClass],
     ) -> (HashMap<ClassInfoClass, Arc<ClassInfo>>, Vec<&'a ClassInfoClass>) {
         let mut inner = self.inner.lock().unwrap();
@@ -61,27 +114,177 @@ impl ClassInfoCache {
         classes
             .iter()
             .fold((HashMap::new(), Vec::new()), |mut output, class| {
-                if let Some(classinfo) = inner.get(class).map(Arc::clone) {
+                let key = (language, *class);
+                
+                match inner.classinfos.get(&key).map(Arc::clone) {
                     // Insert into the map if a classinfo exists in the cache.
-                    output.0.insert(*class, classinfo);
-                } else {
+                    Some(classinfo) if !classinfo.is_cache_expired() => {
+                        output.0.insert(*class, classinfo);
+                    },
+                    // Expired classinfos are removed so they are fetched again.
+                    Some(_) => {
+                        inner.classinfos.remove(&key);
+                        output.1.push(class);
+                    },
                     // Collect the classes that were not found in the cache.
-                    output.1.push(class);
+                    None => {
+                        output.1.push(class);
+                    },
                 }
                 
                 output
             })
     }
     
-    /// Inserts a [`HashMap`] of [`ClassInfo`] data into the cache.
+    /// Inserts a [`HashMap`] of [`ClassInfo`] data in `language` into the cache.
     pub fn insert_map(
         &self,
+        language: Language,
         classinfos: HashMap<ClassInfoClass, Arc<ClassInfo>>,
     ) {
         let mut inner = self.inner.lock().unwrap();
         
         for (class, classinfo) in classinfos {
-            inner.insert(class, classinfo);
+            inner.classinfos.insert((language, class), classinfo);
+        }
+    }
+    
+    /// Removes the classinfo for `class` in every language. Classinfos for the class saved to the 
+    /// [`ClassInfoStore`] before now are ignored, so the class is fetched from Steam again.
+    pub fn invalidate(
+        &self,
+        class: ClassInfoClass,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        
+        inner.remove_where(|cached| *cached == class);
+        inner.invalidated.insert(class, time::get_server_time_now());
+    }
+    
+    /// Removes the classinfos for every class of `appid` in every language. Classinfos for the 
+    /// app saved to the [`ClassInfoStore`] before now are ignored, so classes of the app are 
+    /// fetched from Steam again.
+    pub fn invalidate_app(
+        &self,
+        appid: AppId,
+    ) {
+        let mut inner = self.inner.lock().unwrap();
+        
+        inner.remove_where(|(cached_appid, _classid, _instanceid)| *cached_appid == appid);
+        // Invalidations of the app's classes are covered by the app's invalidation.
+        inner.invalidated.retain(|(invalidated_appid, _classid, _instanceid), _invalidated_at| {
+            *invalidated_appid != appid
+        });
+        inner.invalidated_apps.insert(appid, time::get_server_time_now());
+    }
+    
+    /// Checks whether a classinfo for `class` saved to a store at `saved_at` was saved before the 
+    /// class was invalidated. Classinfos without a save time are stale if the class was ever 
+    /// invalidated.
+    pub(crate) fn is_stale(
+        &self,
+        class: &ClassInfoClass,
+        saved_at: Option<ServerTime>,
+    ) -> bool {
+        let Some(invalidated_at) = self.inner.lock().unwrap().invalidated_at(class) else {
+            return false;
+        };
+        
+        match saved_at {
+            Some(saved_at) => saved_at <= invalidated_at,
+            None => true,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::fake_classinfo;
+    
+    #[test]
+    fn keys_classinfos_by_language() {
+        let classinfo_cache = ClassInfoCache::default();
+        let class = (440, 101, None);
+        let classes = [class];
+        
+        classinfo_cache.insert_map(Language::German, HashMap::from([
+            (class, Arc::new(fake_classinfo(101, "Schlüssel"))),
+        ]));
+        
+        let (english, misses) = classinfo_cache.get_map(Language::English, &classes);
+        
+        assert!(english.is_empty());
+        assert_eq!(misses, vec![&class]);
+        
+        let (german, misses) = classinfo_cache.get_map(Language::German, &classes);
+        
+        assert_eq!(german[&class].name, "Schlüssel");
+        assert!(misses.is_empty());
+    }
+    
+    #[test]
+    fn treats_expired_classinfos_as_misses() {
+        let classinfo_cache = ClassInfoCache::default();
+        let class = (440, 101, None);
+        let classes = [class];
+        let mut classinfo = fake_classinfo(101, "Key");
+        
+        classinfo.cache_expiration = Some(crate::time::get_server_time_now());
+        classinfo_cache.insert_map(Language::English, HashMap::from([
+            (class, Arc::new(classinfo)),
+        ]));
+        
+        let (map, misses) = classinfo_cache.get_map(Language::English, &classes);
+        
+        assert!(map.is_empty());
+        assert_eq!(misses, vec![&class]);
+    }
+    
+    #[test]
+    fn invalidates_classes_saved_before_invalidation() {
+        let classinfo_cache = ClassInfoCache::default();
+        let key = (440, 101, None);
+        let hat = (440, 102, None);
+        let card = (753, 103, None);
+        let classes = [key, hat, card];
+        
+        for language in [Language::English, Language::German] {
+            classinfo_cache.insert_map(language, HashMap::from([
+                (key, Arc::new(fake_classinfo(101, "Key"))),
+                (hat, Arc::new(fake_classinfo(102, "Hat"))),
+                (card, Arc::new(fake_classinfo(103, "Card"))),
+            ]));
+        }
+        
+        let before = time::get_server_time_now();
+        
+        classinfo_cache.invalidate(key);
+        
+        for language in [Language::English, Language::German] {
+            let (_map, misses) = classinfo_cache.get_map(language, &classes);
+            
+            assert_eq!(misses, vec![&key]);
+        }
+        
+        let after = time::get_server_time_now() + chrono::Duration::try_seconds(1).unwrap();
+        
+        assert!(classinfo_cache.is_stale(&key, Some(before)));
+        assert!(classinfo_cache.is_stale(&key, None));
+        assert!(!classinfo_cache.is_stale(&key, Some(after)));
+        assert!(!classinfo_cache.is_stale(&hat, Some(before)));
+        
+        classinfo_cache.invalidate_app(440);
+        
+        let (map, misses) = classinfo_cache.get_map(Language::English, &classes);
+        
+        assert_eq!(map.len(), 1);
+        assert_eq!(misses, vec![&key, &hat]);
+        assert!(classinfo_cache.is_stale(&hat, Some(before)));
+        assert!(classinfo_cache.is_stale(&(440, 104, None), Some(before)));
+        assert!(!classinfo_cache.is_stale(&hat, Some(after)));
+        assert!(!classinfo_cache.is_stale(&card, None));
+        // Invalidations of the app's classes are replaced by the app's invalidation.
+        assert!(classinfo_cache.inner.lock().unwrap().invalidated.is_empty());
+    }
+}
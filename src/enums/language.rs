@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 /// Languages used in API calls.
 /// 
 /// See <https://partner.steamgames.com/doc/store/localization/languages> for more information.
-#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Language {
     /// Arabic language.
     Arabic,
@@ -60,6 +60,22 @@ pub enum Error {
     MalformedResponse(&'static str),
 }
 
+impl Error {
+    /// Whether the error is likely temporary, such as a timeout, a connection error, or Steam's 
+    /// servers being unavailable. Requests failing with these errors may succeed if retried. 
+    /// Note that actions on trade offers failing with [`TradeOfferError::Timeout`] may have 
+    /// succeeded.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Reqwest(error) => error.is_timeout() || error.is_connect(),
+            Self::StatusCode(status) => crate::retry::is_retryable_status(*status),
+            Self::TradeOffer(TradeOfferError::Timeout) |
+            Self::TradeOffer(TradeOfferError::ServiceUnavailable) => true,
+            _ => false,
+        }
+    }
+}
+
 /// Any number of issues with a provided parameter.
 #[derive(thiserror::Error, Debug)]
 pub enum ParameterError {
@@ -110,6 +126,9 @@ pub enum FileError {
     /// Error with system time.
     #[error("System time failure: {}", .0)]
     SystemTime(#[from] std::time::SystemTimeError),
+    /// The data was written in a newer format version than this version of the crate supports.
+    #[error("Unsupported data version: {}", .0)]
+    UnsupportedVersion(u32),
 }
 
 /// An error received from a response when sending or acting of trade offers.
@@ -140,7 +159,7 @@ pub enum TradeOfferError {
     /// - You aren't friends with the other user and you didn't provide a trade token.
     /// - The provided trade token was wrong.
     /// - You are trying to send or receive an item for a game in which you or the other user 
-    /// can't trade (e.g. due to a VAC ban).
+    ///   can't trade (e.g. due to a VAC ban).
     /// - You are trying to send an item and the other user's inventory is full for that game.
     #[error("AccessDenied")]
     AccessDenied,
@@ -1,4 +1,6 @@
 use crate::types::Client;
+use crate::rate_limiter::RateLimiter;
+use crate::retry::RetryPolicy;
 use crate::error::{TradeOfferError, Error};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -12,14 +14,25 @@ use async_fs::File;
 use futures::io::AsyncWriteExt;
 use lazy_static::lazy_static;
 use directories::BaseDirs;
+use url::Url;
 
 lazy_static! {
+    /// The default base URL for Steam Community requests.
+    pub static ref DEFAULT_COMMUNITY_URL: Url = format!("https://{COMMUNITY_HOSTNAME}").parse::<Url>()
+        // Should not panic since the URL is hardcoded.
+        .unwrap_or_else(|error| panic!("URL could not be parsed from {COMMUNITY_HOSTNAME}: {error}"));
+    /// The default base URL for Steam Web API requests.
+    pub static ref DEFAULT_WEB_API_URL: Url = format!("https://{WEB_API_HOSTNAME}").parse::<Url>()
+        // Should not panic since the URL is hardcoded.
+        .unwrap_or_else(|error| panic!("URL could not be parsed from {WEB_API_HOSTNAME}: {error}"));
     pub static ref DEFAULT_CLIENT: Client = {
         let cookie_store = Arc::new(Jar::default());
         
         get_default_middleware(
             cookie_store,
             USER_AGENT_STRING,
+            None,
+            None,
         )
     };
 }
@@ -37,6 +50,15 @@ pub const USER_AGENT_STRING: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit
 pub(crate) const COMMUNITY_HOSTNAME: &str = "steamcommunity.com";
 pub(crate) const WEB_API_HOSTNAME: &str = "api.steampowered.com";
 
+/// Joins a pathname to a base URL. The base URL may include a path, e.g. when requests are sent 
+/// through a proxy.
+pub fn join_url(
+    base_url: &Url,
+    pathname: &str,
+) -> String {
+    format!("{}{pathname}", base_url.as_str().trim_end_matches('/'))
+}
+
 /// Generates a random sessionid.
 pub fn generate_sessionid() -> String {
     // Should look like "37bf523a24034ec06c60ec61"
@@ -96,10 +118,13 @@ pub async fn write_file_atomic(
     }
 }
 
-/// Creates a client middleware which includes a cookie store and user agent string.
+/// Creates a client middleware which includes a cookie store and user agent string. Requests are 
+/// limited using `rate_limiter` and retried using `retry_policy` when given.
 pub fn get_default_middleware<T>(
     cookie_store: Arc<T>,
     user_agent_string: &'static str,
+    rate_limiter: Option<RateLimiter>,
+    retry_policy: Option<RetryPolicy>,
 ) -> ClientWithMiddleware
 where
     T: CookieStore + 'static,
@@ -114,8 +139,18 @@ where
         .build()
         .unwrap();
     
-    ClientBuilder::new(client)
-        .build()
+    let mut builder = ClientBuilder::new(client);
+    
+    // Retries are added first so that each attempt goes through the rate limiter.
+    if let Some(retry_policy) = retry_policy {
+        builder = builder.with(retry_policy);
+    }
+    
+    if let Some(rate_limiter) = rate_limiter {
+        builder = builder.with(rate_limiter);
+    }
+    
+    builder.build()
 }
 
 /// Checks if location is login.
@@ -184,4 +219,12 @@ mod tests {
         
         assert_eq!(sessionid.len(), 24);
     }
+    
+    #[test]
+    fn joins_urls() {
+        let base_url = "http://127.0.0.1:8080/steam/".parse::<Url>().unwrap();
+        
+        assert_eq!(join_url(&DEFAULT_COMMUNITY_URL, "/tradeoffer/new"), "https://steamcommunity.com/tradeoffer/new");
+        assert_eq!(join_url(&base_url, "/tradeoffer/new"), "http://127.0.0.1:8080/steam/tradeoffer/new");
+    }
 }
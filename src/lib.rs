@@ -12,10 +12,11 @@
 //! - Loading inventories.
 //! - Trade history.
 //! - Helper method for getting your Steam Web API key.
-//! - Automatically cancels offers past a set duration during polls.
+//! - Automatically cancels offers past a set duration or over a set count during polls.
 //! - Loads descriptions (classinfos) for assets. Classinfos are cached to file and read when available. The manager holds a [Least frequently used (LFU) cache](https://en.wikipedia.org/wiki/Least_frequently_used) of classinfos in memory to reduce file reads.
 //! - Uses [tokio](https://crates.io/crates/tokio) asynchronous runtime for performing polling.
 //! - Trade items <em>blazingly fast!</em>
+//! - Offline fake Steam server for testing behind the `test-util` feature.
 //! 
 //! ## Usage
 //! 
@@ -33,6 +34,8 @@ mod helpers;
 mod classinfo_cache;
 mod time;
 mod static_functions;
+mod rate_limiter;
+mod retry;
 
 pub mod error;
 pub mod request;
@@ -41,21 +44,58 @@ pub mod enums;
 pub mod types;
 pub mod api;
 pub mod mobile_api;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
 pub use static_functions::get_inventory;
 pub use classinfo_cache::ClassInfoCache;
-pub use manager::{TradeOfferManager, TradeOfferManagerBuilder};
+pub use rate_limiter::{RateLimiter, RateLimit};
+pub use retry::RetryPolicy;
+pub use manager::{TradeOfferManager, TradeOfferManagerBuilder, AccountPool};
 
 pub mod polling {
     //! Models related to polling trade offers.
+    pub use super::manager::{AccountPollEvent, AccountPollReceiver};
     pub use super::manager::polling::{
-        Poll,
-        Result,
+        PollEvent,
         PollAction,
         PollType,
         PollOptions,
+        PollBackoff,
+        PollStatus,
+        PendingEscrow,
+        CancelPolicy,
+        CancelDurationPolicy,
+        CancelReason,
         PollReceiver,
         PollSender,
+        RecvError,
+        PollData,
+        PollDataStore,
+        FilePollDataStore,
+        MemoryPollDataStore,
+    };
+}
+
+pub mod offer_store {
+    //! Stores for recording trade offers as they pass through the poller.
+    pub use super::manager::offer_store::{
+        OfferStore,
+        OfferQuery,
+        StoredOffer,
+        StateChange,
+        MemoryOfferStore,
+        FileOfferStore,
+    };
+}
+
+pub mod classinfo_store {
+    //! Stores for persisting classinfos between runs.
+    pub use super::classinfo_cache::{
+        ClassInfoStore,
+        StoredClassInfo,
+        FileClassInfoStore,
+        AppendOnlyClassInfoStore,
     };
 }
 
@@ -0,0 +1,324 @@
+use super::{TradeOfferManager, TradeOfferManagerBuilder};
+use super::polling::{PollOptions, PollEvent, PollSchedule, RecvError};
+use crate::SteamID;
+use crate::{ClassInfoCache, RateLimiter};
+use crate::error::Error;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{broadcast, Semaphore};
+use tokio::task::JoinHandle;
+
+// How many events are buffered for each receiver before it starts lagging.
+const ACCOUNT_POLL_EVENT_CAPACITY: usize = 1024;
+
+/// A [`PollEvent`] tagged with the account it belongs to.
+#[derive(Debug, Clone)]
+pub struct AccountPollEvent {
+    /// The account's [`SteamID`].
+    pub steamid: SteamID,
+    /// The event.
+    pub event: PollEvent,
+}
+
+/// The receiver for polling events from every account in an [`AccountPool`].
+pub type AccountPollReceiver = broadcast::Receiver<AccountPollEvent>;
+
+/// Polls offers for many accounts in one process.
+/// 
+/// Accounts added to the pool share a single [`ClassInfoCache`] and a single [`RateLimiter`] as 
+/// their request budget. When polling is started, the first poll for each account is staggered 
+/// across the poll interval so that accounts do not poll at the same moments, and the number of 
+/// polls performed at the same time across all accounts is limited. Events from every account 
+/// are merged into a single stream tagged with the account's [`SteamID`].
+/// 
+/// # Examples
+/// ```no_run
+/// use steam_tradeoffer_manager::{TradeOfferManager, AccountPool};
+/// use steam_tradeoffer_manager::polling::PollOptions;
+/// 
+/// #[tokio::main]
+/// async fn main() {
+///     let pool = AccountPool::new(PollOptions::default());
+///     
+///     for cookies in [vec!["steamLoginSecure=blahblahblah".to_string()]] {
+///         pool.add_account(TradeOfferManager::builder()
+///             .api_key("00000000000000000000000000000000".to_string())
+///             .cookies(cookies)).unwrap();
+///     }
+///     
+///     let mut receiver = pool.start_polling().unwrap();
+///     
+///     while let Ok(account_event) = receiver.recv().await {
+///         println!("{}: {:?}", u64::from(account_event.steamid), account_event.event);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AccountPool {
+    /// The options used for polling each account.
+    options: PollOptions,
+    /// The cache shared between every account.
+    classinfo_cache: ClassInfoCache,
+    /// The rate limiter shared between every account.
+    rate_limiter: RateLimiter,
+    /// Limits how many polls are performed at the same time.
+    permits: Arc<Semaphore>,
+    /// The managers for each account.
+    managers: Mutex<Vec<TradeOfferManager>>,
+    /// Merged events from every account.
+    events: broadcast::Sender<AccountPollEvent>,
+    /// Tasks forwarding events from each account into `events`.
+    forwarders: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl AccountPool {
+    /// The default number of polls performed at the same time across all accounts.
+    pub const DEFAULT_MAX_CONCURRENT_POLLS: usize = 4;
+    
+    /// Creates a new [`AccountPool`] polling each account using `options`.
+    pub fn new(options: PollOptions) -> Self {
+        let (events, _receiver) = broadcast::channel(ACCOUNT_POLL_EVENT_CAPACITY);
+        
+        Self {
+            options,
+            classinfo_cache: ClassInfoCache::default(),
+            rate_limiter: RateLimiter::default(),
+            permits: Arc::new(Semaphore::new(Self::DEFAULT_MAX_CONCURRENT_POLLS)),
+            managers: Mutex::new(Vec::new()),
+            events,
+            forwarders: Mutex::new(Vec::new()),
+        }
+    }
+    
+    /// The [`ClassInfoCache`] shared between every account. Defaults to a 
+    /// [`ClassInfoCache::default`].
+    pub fn classinfo_cache(mut self, classinfo_cache: ClassInfoCache) -> Self {
+        self.classinfo_cache = classinfo_cache;
+        self
+    }
+    
+    /// The [`RateLimiter`] shared between every account, which acts as the request budget for the 
+    /// pool. Defaults to a [`RateLimiter::default`].
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+    
+    /// The maximum number of polls performed at the same time across all accounts. Default is 
+    /// [`AccountPool::DEFAULT_MAX_CONCURRENT_POLLS`].
+    pub fn max_concurrent_polls(mut self, max_concurrent_polls: usize) -> Self {
+        self.permits = Arc::new(Semaphore::new(max_concurrent_polls.max(1)));
+        self
+    }
+    
+    /// Builds a manager for an account using the pool's [`ClassInfoCache`] and [`RateLimiter`] and 
+    /// adds it to the pool. Returns the manager for performing actions on the account. If polling 
+    /// has already started, call [`AccountPool::start_polling`] again to include the account.
+    /// 
+    /// # Errors
+    /// If the cookies are not set. (See [`TradeOfferManagerBuilder::cookies`])
+    pub fn add_account(
+        &self,
+        builder: TradeOfferManagerBuilder,
+    ) -> Result<TradeOfferManager, Error> {
+        let manager = builder
+            .classinfo_cache(self.classinfo_cache.clone())
+            .rate_limiter(self.rate_limiter.clone())
+            .build();
+        let steamid = manager.get_steamid()?;
+        let mut managers = self.managers.lock().unwrap();
+        
+        // Replace the manager if the account was already added.
+        managers.retain(|manager| manager.get_steamid().ok() != Some(steamid));
+        managers.push(manager.clone());
+        
+        Ok(manager)
+    }
+    
+    /// Removes an account from the pool. Polling for the account is stopped.
+    pub fn remove_account(
+        &self,
+        steamid: SteamID,
+    ) -> Option<TradeOfferManager> {
+        let mut managers = self.managers.lock().unwrap();
+        let index = managers
+            .iter()
+            .position(|manager| manager.get_steamid().ok() == Some(steamid))?;
+        let manager = managers.remove(index);
+        
+        manager.stop_polling();
+        Some(manager)
+    }
+    
+    /// Gets the managers for every account in the pool.
+    pub fn managers(&self) -> Vec<TradeOfferManager> {
+        self.managers.lock().unwrap().clone()
+    }
+    
+    /// Starts polling every account in the pool. Polling for accounts which are already polling is 
+    /// restarted. Returns a receiver for events from every account. Additional receivers can be 
+    /// obtained using [`AccountPool::subscribe`].
+    /// 
+    /// # Errors
+    /// If polling could not be started for an account. (See 
+    /// [`TradeOfferManager::start_polling`]) Accounts started before the error continue polling.
+    pub fn start_polling(&self) -> Result<AccountPollReceiver, Error> {
+        let receiver = self.events.subscribe();
+        let managers = self.managers();
+        let mut forwarders = self.forwarders.lock().unwrap();
+        
+        for forwarder in forwarders.drain(..) {
+            forwarder.abort();
+        }
+        
+        let poll_interval = self.options.poll_interval.to_std().unwrap_or_default();
+        let count = managers.len() as u32;
+        
+        for (index, manager) in managers.iter().enumerate() {
+            let steamid = manager.get_steamid()?;
+            let schedule = PollSchedule {
+                // Spread the first polls evenly across the interval.
+                start_delay: poll_interval * index as u32 / count,
+                permits: Some(Arc::clone(&self.permits)),
+            };
+            let (_sender, account_receiver) = manager.start_polling_with_schedule(
+                self.options.clone(),
+                schedule,
+            )?;
+            
+            forwarders.push(tokio::spawn(forward_events(
+                steamid,
+                account_receiver,
+                self.events.clone(),
+            )));
+        }
+        
+        Ok(receiver)
+    }
+    
+    /// Subscribes to events from every account in the pool.
+    pub fn subscribe(&self) -> AccountPollReceiver {
+        self.events.subscribe()
+    }
+    
+    /// Stops polling every account in the pool.
+    pub fn stop_polling(&self) {
+        for manager in self.managers() {
+            manager.stop_polling();
+        }
+        
+        if let Ok(mut forwarders) = self.forwarders.lock() {
+            for forwarder in forwarders.drain(..) {
+                forwarder.abort();
+            }
+        }
+    }
+}
+
+impl std::ops::Drop for AccountPool {
+    fn drop(&mut self) {
+        if let Ok(mut forwarders) = self.forwarders.lock() {
+            for forwarder in forwarders.drain(..) {
+                forwarder.abort();
+            }
+        }
+    }
+}
+
+/// Forwards events from an account into the merged stream until polling for the account ends.
+async fn forward_events(
+    steamid: SteamID,
+    mut receiver: broadcast::Receiver<PollEvent>,
+    sender: broadcast::Sender<AccountPollEvent>,
+) {
+    loop {
+        match receiver.recv().await {
+            Ok(event) => {
+                // This only fails if there are no receivers.
+                let _ = sender.send(AccountPollEvent {
+                    steamid,
+                    event,
+                });
+            },
+            Err(RecvError::Lagged(count)) => {
+                log::warn!("Skipped {count} poll events for {}", u64::from(steamid));
+            },
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{FakeSteam, FakeRoute};
+    use std::time::Duration;
+    
+    const TIMEOUT: Duration = Duration::from_secs(10);
+    
+    /// Options which poll once for each account then wait an hour.
+    fn options() -> PollOptions {
+        PollOptions {
+            poll_interval: chrono::Duration::try_hours(1).unwrap(),
+            ..PollOptions::default()
+        }
+    }
+    
+    /// Receives events until a poll for `steamid` completes.
+    async fn recv_poll_complete(
+        receiver: &mut AccountPollReceiver,
+        steamid: SteamID,
+    ) {
+        tokio::time::timeout(TIMEOUT, async {
+            loop {
+                let account_event = receiver.recv().await.unwrap();
+                
+                if account_event.steamid == steamid && account_event.event.is_end_of_poll() {
+                    return;
+                }
+            }
+        }).await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn staggers_first_polls_across_interval() {
+        let steamid = SteamID::from(76561198000000001);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let other_steam = FakeSteam::start(SteamID::from(76561198000000002)).unwrap();
+        let pool = AccountPool::new(options());
+        
+        pool.add_account(steam.manager_builder()).unwrap();
+        pool.add_account(other_steam.manager_builder()).unwrap();
+        
+        let mut receiver = pool.start_polling().unwrap();
+        
+        recv_poll_complete(&mut receiver, steamid).await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        
+        // The other account does not poll until half of the interval has passed.
+        assert_eq!(steam.state().request_count(FakeRoute::GetTradeOffers), 1);
+        assert_eq!(other_steam.state().request_count(FakeRoute::GetTradeOffers), 0);
+        pool.stop_polling();
+    }
+    
+    #[tokio::test]
+    async fn limits_concurrent_polls() {
+        let steamid = SteamID::from(76561198000000001);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let pool = AccountPool::new(options())
+            .max_concurrent_polls(1);
+        
+        pool.add_account(steam.manager_builder()).unwrap();
+        
+        // Holds the only permit, as if another account were polling.
+        let permit = Arc::clone(&pool.permits).acquire_owned().await.unwrap();
+        let mut receiver = pool.start_polling().unwrap();
+        
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(steam.state().request_count(FakeRoute::GetTradeOffers), 0);
+        
+        drop(permit);
+        recv_poll_complete(&mut receiver, steamid).await;
+        assert_eq!(steam.state().request_count(FakeRoute::GetTradeOffers), 1);
+        pool.stop_polling();
+    }
+}
@@ -1,12 +1,22 @@
 use super::TradeOfferManager;
+use super::polling::PollDataStore;
+use super::offer_store::OfferStore;
+use crate::api::TradeOfferApi;
+use crate::mobile_api::ConfirmationApi;
 use crate::helpers::USER_AGENT_STRING;
+use crate::helpers::{DEFAULT_COMMUNITY_URL, DEFAULT_WEB_API_URL};
+use crate::{RateLimiter, RetryPolicy};
 use crate::helpers::default_data_directory;
 use crate::ClassInfoCache;
+use crate::classinfo_store::ClassInfoStore;
 use crate::enums::Language;
+use crate::error::Error;
+use crate::static_functions::get_api_key;
 use std::path::PathBuf;
 use std::sync::Arc;
 use reqwest::cookie::Jar;
 use reqwest_middleware::ClientWithMiddleware;
+use url::Url;
 
 /// Builder for constructing a [`TradeOfferManager`].
 /// 
@@ -39,16 +49,34 @@ pub struct TradeOfferManagerBuilder {
     pub(crate) classinfo_cache: Option<ClassInfoCache>,
     /// The location to save data to.
     pub(crate) data_directory: PathBuf,
+    /// The storage backend for classinfos.
+    pub(crate) classinfo_store: Option<Arc<dyn ClassInfoStore>>,
     /// Request cookies.
     pub(crate) cookie_jar: Option<Arc<Jar>>,
     /// Client to use for requests. Remember to also include the cookies connected to this client.
     pub(crate) client: Option<ClientWithMiddleware>,
     /// User agent for requests.
     pub(crate) user_agent: &'static str,
+    /// The base URL for Steam Community requests.
+    pub(crate) community_url: Url,
+    /// The base URL for Steam Web API requests.
+    pub(crate) web_api_url: Url,
+    /// Limits the rate of requests sent by the default client.
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    /// Retries requests which fail due to transient errors.
+    pub(crate) retry_policy: Option<RetryPolicy>,
     /// How many seconds your computer is behind Steam's servers. Used in mobile confirmations.
     pub(crate) time_offset: i64,
     /// Cookies to set on initialization.
     pub(crate) cookies: Option<Vec<String>>,
+    /// The storage backend for poll data.
+    pub(crate) poll_data_store: Option<Arc<dyn PollDataStore>>,
+    /// The store for recording offers from polls.
+    pub(crate) offer_store: Option<Arc<dyn OfferStore>>,
+    /// The API for trade offer requests.
+    pub(crate) trade_offer_api: Option<Arc<dyn TradeOfferApi>>,
+    /// The API for mobile confirmation requests.
+    pub(crate) confirmation_api: Option<Arc<dyn ConfirmationApi>>,
 }
 
 impl Default for TradeOfferManagerBuilder {
@@ -66,11 +94,20 @@ impl TradeOfferManagerBuilder {
             language: Language::English,
             classinfo_cache: None,
             data_directory: default_data_directory(),
+            classinfo_store: None,
             cookie_jar: None,
             client: None,
             user_agent: USER_AGENT_STRING,
+            community_url: DEFAULT_COMMUNITY_URL.clone(),
+            web_api_url: DEFAULT_WEB_API_URL.clone(),
+            rate_limiter: None,
+            retry_policy: None,
             time_offset: 0,
             cookies: None,
+            poll_data_store: None,
+            offer_store: None,
+            trade_offer_api: None,
+            confirmation_api: None,
         }
     }
     
@@ -118,6 +155,42 @@ impl TradeOfferManagerBuilder {
         self
     }
     
+    /// Limits the rate of requests sent by the manager and its poller. The same budgets are used 
+    /// for trade offer requests and mobile confirmations. Requests are not limited if this is not 
+    /// set. See [`RateLimiter`] for sharing budgets between managers and for using it with a 
+    /// client provided using `client`.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+    
+    /// Retries requests which fail due to transient errors using `retry_policy`. Requests which 
+    /// read data are retried by the default client. Sending and accepting offers are retried 
+    /// only after checking that the failed attempt did not take effect. Requests are not retried 
+    /// if this is not set.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+    
+    /// The base URL for Steam Community requests made by the manager, which include sending and 
+    /// responding to offers, inventories, mobile confirmations, and 
+    /// [`get_api_key`][TradeOfferManagerBuilder::get_api_key]. Default is 
+    /// `https://steamcommunity.com`. Useful for sending requests to a proxy or a local server, 
+    /// e.g. `http://127.0.0.1:8080`.
+    pub fn community_url(mut self, community_url: Url) -> Self {
+        self.community_url = community_url;
+        self
+    }
+    
+    /// The base URL for Steam Web API requests, such as getting trade offers. Default is 
+    /// `https://api.steampowered.com`. Useful for sending requests to a proxy or a local server, 
+    /// e.g. `http://127.0.0.1:8080`.
+    pub fn web_api_url(mut self, web_api_url: Url) -> Self {
+        self.web_api_url = web_api_url;
+        self
+    }
+    
     /// How many seconds your computer is behind Steam's servers. Used in mobile confirmations.
     pub fn time_offset(mut self, time_offset: i64) -> Self {
         self.time_offset = time_offset;
@@ -130,6 +203,92 @@ impl TradeOfferManagerBuilder {
         self
     }
     
+    /// The storage backend for classinfos. Defaults to a 
+    /// [`FileClassInfoStore`][crate::classinfo_store::FileClassInfoStore] which stores each 
+    /// classinfo as a file in the data directory.
+    pub fn classinfo_store<T>(mut self, classinfo_store: T) -> Self
+    where
+        T: ClassInfoStore + 'static,
+    {
+        self.classinfo_store = Some(Arc::new(classinfo_store));
+        self
+    }
+    
+    /// The storage backend for poll data. Defaults to a 
+    /// [`FilePollDataStore`][crate::polling::FilePollDataStore] which stores poll data in the 
+    /// data directory.
+    pub fn poll_data_store<T>(mut self, poll_data_store: T) -> Self
+    where
+        T: PollDataStore + 'static,
+    {
+        self.poll_data_store = Some(Arc::new(poll_data_store));
+        self
+    }
+    
+    /// The store for recording snapshots of offers as they pass through the poller. Offers are 
+    /// not recorded if this is not set.
+    pub fn offer_store<T>(mut self, offer_store: T) -> Self
+    where
+        T: OfferStore + 'static,
+    {
+        self.offer_store = Some(Arc::new(offer_store));
+        self
+    }
+    
+    /// The API used for trade offer requests. Defaults to a 
+    /// [`SteamTradeOfferAPI`][crate::api::SteamTradeOfferAPI] configured using this builder. When 
+    /// set, the API key, language, classinfo cache, classinfo store, data directory, client, and 
+    /// base URLs of this builder are not used for trade offer requests. Useful for injecting a 
+    /// stub in tests.
+    pub fn trade_offer_api<T>(mut self, trade_offer_api: T) -> Self
+    where
+        T: TradeOfferApi + 'static,
+    {
+        self.trade_offer_api = Some(Arc::new(trade_offer_api));
+        self
+    }
+    
+    /// The API used for mobile confirmation requests. Defaults to a 
+    /// [`MobileAPI`][crate::mobile_api::MobileAPI] configured using this builder. When set, the 
+    /// identity secret, time offset, client, and community URL of this builder are not used for 
+    /// confirmation requests. Useful for injecting a stub in tests.
+    pub fn confirmation_api<T>(mut self, confirmation_api: T) -> Self
+    where
+        T: ConfirmationApi + 'static,
+    {
+        self.confirmation_api = Some(Arc::new(confirmation_api));
+        self
+    }
+    
+    /// Gets your Steam Web API key using the community URL of this builder. See 
+    /// [`TradeOfferManager::get_api_key`].
+    /// 
+    /// # Examples
+    /// ```no_run
+    /// use steam_tradeoffer_manager::TradeOfferManager;
+    /// 
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     // You'll need to use your own cookies here.
+    ///     let cookies = vec![
+    ///         "sessionid=blahblahblah".to_string(),
+    ///         "steamLoginSecure=blahblahblah".to_string(),
+    ///     ];
+    ///     let builder = TradeOfferManager::builder()
+    ///         .community_url("http://127.0.0.1:8080".parse().unwrap());
+    ///     let api_key = builder.get_api_key(&cookies).await.unwrap();
+    ///     let manager = builder
+    ///         .api_key(api_key)
+    ///         .build();
+    /// }
+    /// ```
+    pub async fn get_api_key(
+        &self,
+        cookies: &[String],
+    ) -> Result<String, Error> {
+        get_api_key(cookies, &self.community_url).await
+    }
+    
     /// Builds the [`TradeOfferManager`].
     pub fn build(self) -> TradeOfferManager {
         self.into()
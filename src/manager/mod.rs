@@ -1,39 +1,62 @@
 mod builder;
+mod account_pool;
 pub(crate) mod polling;
+pub(crate) mod offer_store;
+mod offer_data;
 
 pub use builder::TradeOfferManagerBuilder;
-use polling::{Polling, PollOptions, PollReceiver, PollSender};
+use offer_store::OfferStore;
+use offer_data::OfferData;
+pub use account_pool::{AccountPool, AccountPollEvent, AccountPollReceiver};
+use polling::{Polling, PollSchedule, PollAction, PollOptions, PollReceiver, PollSender, PollStatus, PendingEscrow, PollDataStore, FilePollDataStore};
 
 use crate::api::request::GetTradeOffersOptions;
 use crate::time;
 use crate::types::ServerTime;
-use crate::api::SteamTradeOfferAPI;
-use crate::mobile_api::MobileAPI;
+use crate::api::{SteamTradeOfferAPI, TradeOfferApi};
+use crate::api::response::{UnmappedTradeOffer, RawTradeOffer};
+use crate::mobile_api::{MobileAPI, ConfirmationApi};
 use crate::static_functions::get_api_key;
-use crate::helpers::{generate_sessionid, get_default_middleware, get_sessionid_and_steamid_from_cookies};
+use crate::retry::{self, RetryPolicy};
+use crate::helpers::{generate_sessionid, get_default_middleware, get_sessionid_and_steamid_from_cookies, DEFAULT_COMMUNITY_URL};
 use crate::error::{ParameterError, Error};
 use crate::request::{NewTradeOffer, GetTradeHistoryOptions};
 use crate::enums::{TradeOfferState, OfferFilter, GetUserDetailsMethod};
 use crate::types::{AppId, ContextId, TradeOfferId};
 use crate::response::{UserDetails, Asset, SentOffer, TradeOffer, AcceptedOffer, Confirmation, Trades};
+use std::collections::HashSet;
 use std::sync::Mutex;
 use std::sync::Arc;
 use std::sync::atomic::{Ordering, AtomicU64};
 use steamid_ng::SteamID;
-use tokio::task::JoinHandle;
+use chrono::Duration;
+
+// How far back to look for an offer that may have been sent despite an error.
+const SEND_OFFER_CLOCK_SKEW_MINUTES: i64 = 5;
 
 /// Manager which includes functionality for interacting with trade offers, confirmations and 
 /// inventories.
 #[derive(Debug, Clone)]
 pub struct TradeOfferManager {
     /// The underlying API.
-    api: SteamTradeOfferAPI,
+    api: Arc<dyn TradeOfferApi>,
     /// The underlying API for mobile confirmations.
-    mobile_api: MobileAPI,
+    mobile_api: Arc<dyn ConfirmationApi>,
     /// The account's SteamID.
     steamid: Arc<AtomicU64>,
-    /// The sender for sending messages to polling, along with the task handle.
-    polling: Arc<Mutex<Option<JoinHandle<()>>>>,
+    /// The storage backend for poll data.
+    poll_data_store: Arc<dyn PollDataStore>,
+    /// The sender for sending messages to polling, along with the task handle. Shared between 
+    /// clones so that polling is aborted once every clone is dropped.
+    polling: Arc<Mutex<Option<Polling>>>,
+    /// Offers sent by this manager which are awaiting mobile confirmation.
+    awaiting_confirmation: Arc<Mutex<HashSet<TradeOfferId>>>,
+    /// The store for recording offers from polls.
+    offer_store: Option<Arc<dyn OfferStore>>,
+    /// Custom data attached to offers.
+    offer_data: Arc<Mutex<OfferData>>,
+    /// Used for retrying sending and accepting offers.
+    retry_policy: Option<RetryPolicy>,
 }
 
 impl TradeOfferManager {
@@ -48,6 +71,9 @@ impl TradeOfferManager {
     /// be created using `localhost` as the domain. By calling this method you are agreeing to the 
     /// [Steam Web API Terms of Use](https://steamcommunity.com/dev/apiterms). 
     /// 
+    /// Requests are sent to `https://steamcommunity.com`. Use 
+    /// [`TradeOfferManagerBuilder::get_api_key`] to use the community URL of a builder.
+    /// 
     /// # Examples
     /// ```no_run
     /// use steam_tradeoffer_manager::TradeOfferManager;
@@ -67,7 +93,7 @@ impl TradeOfferManager {
     pub async fn get_api_key(
         cookies: &[String],
     ) -> Result<String, Error> {
-        get_api_key(cookies).await
+        get_api_key(cookies, &DEFAULT_COMMUNITY_URL).await
     }
     
     /// Sets cookies.
@@ -128,56 +154,61 @@ impl TradeOfferManager {
         Ok(SteamID::from(steamid_64))
     }
     
-    /// Starts polling offers. Listen to the returned receiver for events. Use the returned sender 
-    /// to send an action to the poller using [`steam_tradeoffer_manager::polling::PollAction`].
+    /// Starts polling offers. Listen to the returned receiver for
+    /// [`PollEvent`][crate::polling::PollEvent]s. Use the returned sender to send an action to the
+    /// poller using [`PollAction`][crate::polling::PollAction].
+    /// 
+    /// Additional receivers can be obtained using [`TradeOfferManager::subscribe_polls`]. Every 
+    /// receiver gets every event.
     /// 
-    /// Call `stop_polling` to stop polling offers. Polling will also stop if either the receiver 
-    /// or this [`TradeOfferManager`] are dropped. If this method is called again, the previous 
-    /// polling task will be aborted.
+    /// Call `stop_polling` to stop polling offers. Polling will also stop if every clone of this 
+    /// [`TradeOfferManager`] is dropped or [`PollAction::StopPolling`][crate::polling::PollAction] 
+    /// is sent. Dropping receivers does not stop polling. If this method is called again, the 
+    /// previous polling task will be aborted.
     /// 
     /// # Examples
     /// ```no_run
     /// use steam_tradeoffer_manager::TradeOfferManager;
     /// use steam_tradeoffer_manager::enums::TradeOfferState;
-    /// use steam_tradeoffer_manager::polling::{PollOptions, PollReceiver, Poll};
+    /// use steam_tradeoffer_manager::response::TradeOffer;
+    /// use steam_tradeoffer_manager::polling::{PollOptions, PollReceiver, PollEvent, RecvError};
     /// 
     /// // Polls offers.
     /// async fn poll_offers(
     ///     manager: TradeOfferManager,
-    ///     receiver: PollReceiver,
+    ///     mut receiver: PollReceiver,
     /// ) {
-    ///     while let Some(result) = receiver.recv().await {
-    ///         match result {
-    ///             Ok(offers) => on_poll(&manager, offers).await,
-    ///             Err(error) => println!("Error encountered polling offers: {error}"),
+    ///     loop {
+    ///         match receiver.recv().await {
+    ///             Ok(PollEvent::NewReceivedOffer(offer)) => on_new_offer(&manager, offer).await,
+    ///             Ok(PollEvent::PollFailed(error)) => println!("Error encountered polling offers: {error}"),
+    ///             Ok(_) => {},
+    ///             Err(RecvError::Lagged(count)) => println!("Skipped {count} events"),
+    ///             Err(RecvError::Closed) => break,
     ///         }
     ///     }
     ///     
     ///     println!("Polling stopped");
     /// }
     /// 
-    /// // Do something with offers.
-    /// async fn on_poll(
+    /// // Do something with a new offer.
+    /// async fn on_new_offer(
     ///     manager: &TradeOfferManager,
-    ///     offers: Poll, // Poll is an alias for Vec<(TradeOffer, Option<TradeOfferState>)>
+    ///     mut offer: TradeOffer,
     /// ) {
-    ///     for (mut offer, _old_state) in offers {
-    ///         let is_free_items = {
-    ///             // Offer must be active.
-    ///             offer.trade_offer_state == TradeOfferState::Active &&
-    ///             // Offer must not be created by us.
-    ///             !offer.is_our_offer && 
-    ///             // Offer must not be giving items.
-    ///             offer.items_to_give.is_empty()
-    ///         };
+    ///     let is_free_items = {
+    ///         // Offer must be active.
+    ///         offer.trade_offer_state == TradeOfferState::Active &&
+    ///         // Offer must not be giving items.
+    ///         offer.items_to_give.is_empty()
+    ///     };
+    ///     
+    ///     if is_free_items {
+    ///         println!("{offer} is giving us free items - accepting");
     ///         
-    ///         if is_free_items {
-    ///             println!("{offer} is giving us free items - accepting");
-    ///             
-    ///             match manager.accept_offer(&mut offer).await {
-    ///                 Ok(accepted_offer) => println!("{} Accepted", offer),
-    ///                 Err(error) => println!("Error accepting {offer}: {error}"),
-    ///             }
+    ///         match manager.accept_offer(&mut offer).await {
+    ///             Ok(accepted_offer) => println!("{} Accepted", offer),
+    ///             Err(error) => println!("Error accepting {offer}: {error}"),
     ///         }
     ///     }
     /// }
@@ -202,44 +233,229 @@ impl TradeOfferManager {
     /// # Errors
     /// - If the API key is not set. (See [`TradeOfferManagerBuilder::get_api_key`])
     /// - If the cookies are not set. (See [`TradeOfferManager::set_cookies`])
+    /// - If `confirm_sent_offers` is set and the identity secret is not set. (See
+    ///   [`TradeOfferManagerBuilder::identity_secret`])
     pub fn start_polling(
         &self,
         options: PollOptions,
     ) -> Result<(PollSender, PollReceiver), Error> {
-        if self.api.api_key.is_none() {
+        self.start_polling_with_schedule(options, PollSchedule::default())
+    }
+    
+    /// Starts polling offers using the given schedule.
+    pub(crate) fn start_polling_with_schedule(
+        &self,
+        options: PollOptions,
+        schedule: PollSchedule,
+    ) -> Result<(PollSender, PollReceiver), Error> {
+        if !self.api.has_api_key() {
             return Err(ParameterError::MissingApiKey.into());
         }
         
+        if options.confirm_sent_offers && !self.mobile_api.has_identity_secret() {
+            return Err(ParameterError::NoIdentitySecret.into());
+        }
+        
         let steamid = self.get_steamid()?;
         let mut polling = self.polling.lock().unwrap();
         
-        if let Some(handle) = &*polling {
+        if let Some(polling) = &*polling {
             // Abort the previous polling.
-            handle.abort();
+            polling.handle.abort();
         }
         
-        let Polling {
-            sender,
-            receiver,
-            handle,
-        } = Polling::new(
+        let (new_polling, receiver) = Polling::new(
             steamid,
-            self.api.clone(),
+            self,
             options,
+            schedule,
         );
+        let sender = new_polling.sender.clone();
         
-        *polling = Some(handle);
+        *polling = Some(new_polling);
         
         Ok((sender, receiver))
     }
     
-    /// Stops polling.
+    /// Subscribes to events from polling. Each subscriber receives every event independently of 
+    /// other subscribers. Returns `None` if polling has not been started or has stopped.
+    pub fn subscribe_polls(
+        &self,
+    ) -> Option<PollReceiver> {
+        self.polling.lock().ok()?
+            .as_ref()
+            .and_then(|polling| polling.subscribe())
+    }
+    
+    /// Gets a snapshot of what polling is doing, such as when the last poll ran and how many polls 
+    /// have failed in a row. Returns `None` if polling has not been started or has stopped.
+    pub fn poll_status(
+        &self,
+    ) -> Option<PollStatus> {
+        self.polling.lock().ok()?
+            .as_ref()
+            .filter(|polling| !polling.handle.is_finished())
+            .map(|polling| polling.status())
+    }
+    
+    /// Attaches custom data to an offer, e.g. the ID of an order the offer was sent for. The data 
+    /// is included on offers emitted from polls and replaces any data previously attached to the 
+    /// offer. Data is removed once the offer is emitted in a state it cannot change from, such as 
+    /// [`TradeOfferState::Accepted`][crate::enums::TradeOfferState::Accepted].
+    /// 
+    /// Data is saved in the poll data. While polling, it is saved along with the poll data. 
+    /// Otherwise, it is saved to the [`PollDataStore`] right away.
+    pub async fn set_offer_data(
+        &self,
+        tradeofferid: TradeOfferId,
+        data: serde_json::Value,
+    ) {
+        self.load_offer_data().await;
+        
+        {
+            let mut offer_data = self.offer_data.lock().unwrap();
+            
+            offer_data.map.insert(tradeofferid, data);
+            offer_data.changed = true;
+        }
+        
+        self.save_offer_data().await;
+    }
+    
+    /// Gets the custom data attached to an offer.
+    pub async fn get_offer_data(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Option<serde_json::Value> {
+        self.load_offer_data().await;
+        self.offer_data.lock().unwrap().map.get(&tradeofferid).cloned()
+    }
+    
+    /// Removes the custom data attached to an offer.
+    pub async fn remove_offer_data(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Option<serde_json::Value> {
+        self.load_offer_data().await;
+        
+        let data = self.offer_data.lock().unwrap().remove(tradeofferid);
+        
+        if data.is_some() {
+            self.save_offer_data().await;
+        }
+        
+        data
+    }
+    
+    /// Loads the custom offer data saved in the poll data if it has not been loaded yet.
+    async fn load_offer_data(&self) {
+        if self.offer_data.lock().unwrap().is_loaded {
+            return;
+        }
+        
+        let Ok(steamid) = self.get_steamid() else {
+            return;
+        };
+        
+        match self.poll_data_store.load(steamid).await {
+            Ok(poll_data) => {
+                let mut offer_data = self.offer_data.lock().unwrap();
+                
+                // The poller may have loaded the data in the meantime.
+                if !offer_data.is_loaded {
+                    offer_data.load(poll_data.map(|poll_data| poll_data.offer_data).unwrap_or_default());
+                }
+            },
+            Err(error) => log::warn!("Error loading offer data: {error}"),
+        }
+    }
+    
+    /// Saves the custom offer data to the poll data store if polling is not running. While 
+    /// polling, the poller saves it along with the poll data.
+    async fn save_offer_data(&self) {
+        if self.poll_status().is_some() {
+            return;
+        }
+        
+        let Ok(steamid) = self.get_steamid() else {
+            return;
+        };
+        let result = async {
+            let mut poll_data = self.poll_data_store.load(steamid).await?
+                .unwrap_or_default();
+            
+            {
+                let mut offer_data = self.offer_data.lock().unwrap();
+                
+                poll_data.offer_data = offer_data.map.clone();
+                offer_data.changed = false;
+            }
+            
+            self.poll_data_store.save(steamid, &poll_data).await
+        }.await;
+        
+        if let Err(error) = result {
+            log::warn!("Error saving offer data: {error}");
+        }
+    }
+    
+    /// Gets the store offers from polls are recorded in, if one was set using 
+    /// [`TradeOfferManagerBuilder::offer_store`]. Use this for querying offers.
+    pub fn offer_store(
+        &self,
+    ) -> Option<Arc<dyn OfferStore>> {
+        self.offer_store.clone()
+    }
+    
+    /// Gets the offers in escrow tracked by polling, ordered by when their items are released. 
+    /// Returns `None` if polling has not been started or has stopped.
+    pub fn pending_escrow(
+        &self,
+    ) -> Option<Vec<PendingEscrow>> {
+        self.poll_status().map(|status| status.pending_escrow)
+    }
+    
+    /// Stops polling gracefully. The poll in progress, if any, is allowed to finish and the poll 
+    /// data is saved before the polling task ends. Resolves once the polling task has ended. If 
+    /// the task does not end within `timeout` it is aborted.
+    /// 
+    /// Returns `true` if polling stopped within `timeout`, or `false` if it was aborted.
+    pub async fn stop_polling_gracefully(
+        &self,
+        timeout: Duration,
+    ) -> bool {
+        let polling = match self.polling.lock() {
+            Ok(mut polling) => polling.take(),
+            Err(_) => None,
+        };
+        let Some(mut polling) = polling else {
+            // Polling was never started.
+            return true;
+        };
+        let timeout = timeout.to_std().unwrap_or_default();
+        let stopped = tokio::time::timeout(timeout, async {
+            // This only fails if the task has already ended.
+            let _ = polling.sender.send(PollAction::StopPolling).await;
+            let _ = (&mut polling.handle).await;
+        }).await;
+        
+        if stopped.is_err() {
+            log::warn!("Polling did not stop within {timeout:?} - aborting");
+            polling.handle.abort();
+            return false;
+        }
+        
+        true
+    }
+    
+    /// Stops polling. The poll in progress, if any, is aborted. Use 
+    /// [`TradeOfferManager::stop_polling_gracefully`] to allow it to finish.
     pub fn stop_polling(
         &self,
     ) {
         if let Ok(polling) = self.polling.lock() {
-            if let Some(handle) = &*polling {
-                handle.abort();
+            if let Some(polling) = &*polling {
+                polling.handle.abort();
             }
         }
     }
@@ -247,6 +463,10 @@ impl TradeOfferManager {
     /// Accepts an offer. Updates the state of the offer upon success as long as it does not 
     /// require mobile confirmation.
     /// 
+    /// If a retry policy is set and accepting fails due to a transient error, the offer is 
+    /// checked before retrying. If the offer was accepted despite the error, this returns 
+    /// successfully.
+    /// 
     /// # Errors
     /// - If the offer is ours.
     /// - If the offer is not active.
@@ -265,7 +485,7 @@ impl TradeOfferManager {
             return Err(ParameterError::CannotAcceptOfferThatIsNotActive(offer.trade_offer_state).into());
         }
         
-        let accepted_offer = self.api.accept_offer(offer.tradeofferid, offer.partner).await?;
+        let accepted_offer = self.accept_offer_with_retries(offer.tradeofferid, offer.partner).await?;
         
         // This offer doesn't need confirmation, so we can update its state here. If the 
         // accepted_offer returns without error and does not need confirmation, then we can 
@@ -315,12 +535,22 @@ impl TradeOfferManager {
         Ok(())
     }
     
-    /// Sends an offer.
+    /// Sends an offer. If the offer needs mobile confirmation and polling was started with 
+    /// `confirm_sent_offers`, it will be confirmed during polling.
+    /// 
+    /// If a retry policy is set and sending fails due to a transient error, sent offers are 
+    /// checked for the offer before retrying. If the offer was sent despite the error, it is 
+    /// returned rather than sent again. If sent offers cannot be checked, the original error is 
+    /// returned.
     pub async fn send_offer(
         &self,
         offer: &NewTradeOffer,
     ) -> Result<SentOffer, Error> {
-        self.api.send_offer(offer, None).await
+        let sent_offer = self.send_offer_with_retries(offer, None).await?;
+        
+        self.add_awaiting_confirmation(&sent_offer);
+        
+        Ok(sent_offer)
     }
     
     /// Counters an existing offer. This updates the state of the offer upon success.
@@ -329,16 +559,154 @@ impl TradeOfferManager {
         offer: &mut TradeOffer,
         counter_offer: &NewTradeOffer,
     ) -> Result<SentOffer, Error> {
-        let sent_offer = self.api.send_offer(
+        let sent_offer = self.send_offer_with_retries(
             counter_offer,
             Some(offer.tradeofferid),
         ).await?;
         
         offer.trade_offer_state = TradeOfferState::Countered;
+        self.add_awaiting_confirmation(&sent_offer);
         
         Ok(sent_offer)
     }
     
+    /// Sends an offer, retrying transient errors using the retry policy. Before each retry, sent 
+    /// offers are checked in case the failed attempt went through. Offers which were already sent 
+    /// before the first attempt are not mistaken for the offer. When countering an offer, the 
+    /// countered offer must also be in the [`TradeOfferState::Countered`] state.
+    async fn send_offer_with_retries(
+        &self,
+        offer: &NewTradeOffer,
+        counter_tradeofferid: Option<TradeOfferId>,
+    ) -> Result<SentOffer, Error> {
+        let Some(retry_policy) = self.retry_policy else {
+            return self.api.send_offer(offer, counter_tradeofferid).await;
+        };
+        // Allow for differences between our clock and Steam's.
+        let since = time::get_server_time_now() - Duration::try_minutes(SEND_OFFER_CLOCK_SKEW_MINUTES)
+            // unwrap is safe because the value is in range
+            .unwrap();
+        let existing_tradeofferids = match self.get_sent_offers_since(since).await {
+            Ok(sent_offers) => sent_offers
+                .into_iter()
+                .map(|sent_offer| sent_offer.tradeofferid)
+                .collect::<HashSet<_>>(),
+            // Without knowing which offers were already sent, a retried send cannot be told apart 
+            // from an identical offer sent earlier.
+            Err(error) => {
+                log::debug!("Sending offer without retries: {error}");
+                return self.api.send_offer(offer, counter_tradeofferid).await;
+            },
+        };
+        let mut attempt = 0;
+        
+        loop {
+            let error = match self.api.send_offer(offer, counter_tradeofferid).await {
+                Err(error) if error.is_transient() && attempt < retry_policy.max_retries => error,
+                result => return result,
+            };
+            
+            tokio::time::sleep(retry_policy.delay(attempt)).await;
+            attempt += 1;
+            
+            if let Some(counter_tradeofferid) = counter_tradeofferid {
+                match self.api.get_trade_offer(counter_tradeofferid).await {
+                    // The offer was not countered so the counter offer was not sent.
+                    Ok(countered_offer) => if countered_offer.trade_offer_state != TradeOfferState::Countered {
+                        continue;
+                    },
+                    // Without knowing whether the offer was sent, retrying could send it twice.
+                    Err(_) => return Err(error),
+                }
+            }
+            
+            match self.get_sent_offers_since(since).await {
+                Ok(sent_offers) => {
+                    if let Some(sent_offer) = retry::find_sent_offer(
+                        &sent_offers,
+                        offer,
+                        since,
+                        &existing_tradeofferids,
+                    ) {
+                        log::debug!("Offer {} was sent despite error: {}", sent_offer.tradeofferid, error);
+                        return Ok(retry::sent_offer_from_raw(sent_offer));
+                    }
+                },
+                // Without knowing whether the offer was sent, retrying could send it twice.
+                Err(_) => return Err(error),
+            }
+        }
+    }
+    
+    /// Gets the active offers sent by us since `since`.
+    async fn get_sent_offers_since(
+        &self,
+        since: ServerTime,
+    ) -> Result<Vec<RawTradeOffer>, Error> {
+        let (sent_offers, _descriptions) = self.api.get_raw_trade_offers(&GetTradeOffersOptions {
+            active_only: true,
+            historical_only: false,
+            get_sent_offers: true,
+            get_received_offers: false,
+            get_descriptions: false,
+            historical_cutoff: Some(since),
+        }).await?;
+        
+        Ok(sent_offers)
+    }
+    
+    /// Accepts an offer, retrying transient errors using the retry policy. Before each retry, the 
+    /// offer is checked in case the failed attempt went through.
+    async fn accept_offer_with_retries(
+        &self,
+        tradeofferid: TradeOfferId,
+        partner: SteamID,
+    ) -> Result<AcceptedOffer, Error> {
+        let Some(retry_policy) = self.retry_policy else {
+            return self.api.accept_offer(tradeofferid, partner).await;
+        };
+        let mut attempt = 0;
+        
+        loop {
+            let error = match self.api.accept_offer(tradeofferid, partner).await {
+                Err(error) if error.is_transient() && attempt < retry_policy.max_retries => error,
+                result => return result,
+            };
+            
+            tokio::time::sleep(retry_policy.delay(attempt)).await;
+            attempt += 1;
+            
+            match self.api.get_trade_offer(tradeofferid).await.map(|offer| offer.trade_offer_state) {
+                Ok(TradeOfferState::Accepted) |
+                Ok(TradeOfferState::InEscrow) => {
+                    log::debug!("Offer {} was accepted despite error: {}", tradeofferid, error);
+                    return Ok(AcceptedOffer {
+                        needs_mobile_confirmation: false,
+                        needs_email_confirmation: false,
+                        email_domain: None,
+                    });
+                },
+                // The offer can still be accepted.
+                Ok(TradeOfferState::Active) => {},
+                _ => return Err(error),
+            }
+        }
+    }
+    
+    /// Tracks a sent offer for confirmation during polling if it needs mobile confirmation.
+    fn add_awaiting_confirmation(
+        &self,
+        sent_offer: &SentOffer,
+    ) {
+        if !sent_offer.needs_mobile_confirmation {
+            return;
+        }
+        
+        if let Ok(mut awaiting_confirmation) = self.awaiting_confirmation.lock() {
+            awaiting_confirmation.insert(sent_offer.tradeofferid);
+        }
+    }
+    
     /// Gets our inventory. This method **does not** include untradable items.
     /// 
     /// # Errors
@@ -386,7 +754,7 @@ impl TradeOfferManager {
     ) -> Result<UserDetails, Error> 
         where T: Into<GetUserDetailsMethod>,
     {
-        self.api.get_user_details(partner, method).await
+        self.api.get_user_details(partner, method.into()).await
     }
     
     /// Gets trade confirmations.
@@ -503,26 +871,27 @@ impl TradeOfferManager {
         Ok(())
     }
 
-    /// Gets active trade offers.
+    /// Gets active trade offers. Offers with missing descriptions are returned separately in the 
+    /// 2nd part of the tuple.
     pub async fn get_active_trade_offers(
         &self
-    ) -> Result<Vec<TradeOffer>, Error> {
+    ) -> Result<(Vec<TradeOffer>, Vec<UnmappedTradeOffer>), Error> {
         let historical_cutoff = time::timestamp_to_server_time(u32::MAX as i64);
-        let offers = self.get_trade_offers(
+        
+        self.get_trade_offers(
             OfferFilter::ActiveOnly,
             Some(historical_cutoff),
-        ).await?;
-        
-        Ok(offers)
+        ).await
     }
     
-    /// Gets trade offers. This will trim responses based on the filter. 
+    /// Gets trade offers. This will trim responses based on the filter. Offers with missing 
+    /// descriptions are returned separately in the 2nd part of the tuple.
     pub async fn get_trade_offers(
         &self,
         filter: OfferFilter,
         historical_cutoff: Option<ServerTime>,
-    ) -> Result<Vec<TradeOffer>, Error> {
-        let offers = self.api.get_trade_offers(&GetTradeOffersOptions {
+    ) -> Result<(Vec<TradeOffer>, Vec<UnmappedTradeOffer>), Error> {
+        let (offers, unmapped) = self.api.get_trade_offers(&GetTradeOffersOptions {
             active_only: filter == OfferFilter::ActiveOnly,
             historical_only: filter == OfferFilter::HistoricalOnly,
             get_sent_offers: true,
@@ -530,25 +899,22 @@ impl TradeOfferManager {
             get_descriptions: false,
             historical_cutoff,
         }).await?;
-        
         // trim responses since these don't always return what we want
-        Ok(match filter {
-            OfferFilter::ActiveOnly => {
-                offers
-                    .into_iter()
-                    .filter(|offer| offer.trade_offer_state == TradeOfferState::Active)
-                    .collect::<_>()
-            },
-            OfferFilter::HistoricalOnly => {
-                offers
-                    .into_iter()
-                    .filter(|offer| offer.trade_offer_state != TradeOfferState::Active)
-                    .collect::<_>()
-            },
-            OfferFilter::All => {
-                offers
-            },
-        })
+        let is_included = |trade_offer_state: TradeOfferState| match filter {
+            OfferFilter::ActiveOnly => trade_offer_state == TradeOfferState::Active,
+            OfferFilter::HistoricalOnly => trade_offer_state != TradeOfferState::Active,
+            OfferFilter::All => true,
+        };
+        let offers = offers
+            .into_iter()
+            .filter(|offer| is_included(offer.trade_offer_state))
+            .collect::<Vec<_>>();
+        let unmapped = unmapped
+            .into_iter()
+            .filter(|unmapped| is_included(unmapped.offer.trade_offer_state))
+            .collect::<Vec<_>>();
+        
+        Ok((offers, unmapped))
     }
     
     /// Gets trade history.
@@ -560,17 +926,6 @@ impl TradeOfferManager {
     }
 }
 
-impl std::ops::Drop for TradeOfferManager {
-    fn drop(&mut self) {
-        if let Ok(polling) = self.polling.lock() {
-            if let Some(handle) = &*polling {
-                // Abort polling before dropping.
-                handle.abort();
-            }
-        }
-    }
-}
-
 impl From<TradeOfferManagerBuilder> for TradeOfferManager {
     fn from(builder: TradeOfferManagerBuilder) -> Self {
         let cookies = builder.cookie_jar
@@ -579,32 +934,56 @@ impl From<TradeOfferManagerBuilder> for TradeOfferManager {
             .unwrap_or_else(|| get_default_middleware(
                 Arc::clone(&cookies),
                 builder.user_agent,
+                builder.rate_limiter.map(|rate_limiter| rate_limiter
+                    .community_url(builder.community_url.clone())
+                    .web_api_url(builder.web_api_url.clone())),
+                builder.retry_policy,
             ));
         let steamid = Arc::new(AtomicU64::new(0));
         let classinfo_cache = builder.classinfo_cache.unwrap_or_default();
-        let mut api_builder = SteamTradeOfferAPI::builder()
-            .data_directory(builder.data_directory)
-            .client(client.clone(), Arc::clone(&cookies))
-            .language(builder.language)
-            .classinfo_cache(classinfo_cache);
-        
-        if let Some(api_key) = builder.api_key {
-            api_builder = api_builder.api_key(api_key);   
-        }
-        
-        let mut mobile_api_builder = MobileAPI::builder()
-            .client(client, cookies)
-            .time_offset(builder.time_offset);
-        
-        if let Some(identity_secret) = builder.identity_secret {
-            mobile_api_builder = mobile_api_builder.identity_secret(identity_secret);
-        }
-        
+        let poll_data_store = builder.poll_data_store
+            .unwrap_or_else(|| Arc::new(FilePollDataStore::new(&builder.data_directory)));
+        let api = builder.trade_offer_api.unwrap_or_else(|| {
+            let mut api_builder = SteamTradeOfferAPI::builder()
+                .data_directory(builder.data_directory)
+                .client(client.clone(), Arc::clone(&cookies))
+                .language(builder.language)
+                .classinfo_cache(classinfo_cache)
+                .community_url(builder.community_url.clone())
+                .web_api_url(builder.web_api_url);
+            
+            if let Some(classinfo_store) = builder.classinfo_store {
+                api_builder = api_builder.classinfo_store(classinfo_store);
+            }
+            
+            if let Some(api_key) = builder.api_key {
+                api_builder = api_builder.api_key(api_key);   
+            }
+            
+            Arc::new(api_builder.build())
+        });
+        let mobile_api = builder.confirmation_api.unwrap_or_else(|| {
+            let mut mobile_api_builder = MobileAPI::builder()
+                .client(client, cookies)
+                .community_url(builder.community_url)
+                .time_offset(builder.time_offset);
+            
+            if let Some(identity_secret) = builder.identity_secret {
+                mobile_api_builder = mobile_api_builder.identity_secret(identity_secret);
+            }
+            
+            Arc::new(mobile_api_builder.build())
+        });
         let manager = Self {
             steamid: Arc::clone(&steamid),
-            api: api_builder.build(),
-            mobile_api: mobile_api_builder.build(),
+            api,
+            mobile_api,
+            poll_data_store,
             polling: Arc::new(Mutex::new(None)),
+            awaiting_confirmation: Arc::new(Mutex::new(HashSet::new())),
+            offer_store: builder.offer_store,
+            offer_data: Arc::new(Mutex::new(OfferData::default())),
+            retry_policy: builder.retry_policy,
         };
         
         if let Some(cookies) = builder.cookies {
@@ -0,0 +1,35 @@
+use crate::types::TradeOfferId;
+use std::collections::HashMap;
+use serde_json::Value;
+
+/// Custom data attached to offers, shared between the manager and the poller.
+#[derive(Debug, Default)]
+pub struct OfferData {
+    /// The data for each offer.
+    pub map: HashMap<TradeOfferId, Value>,
+    /// Whether the data has changed since it was last copied into the poll data.
+    pub changed: bool,
+    /// Whether the data saved in the poll data has been loaded.
+    pub is_loaded: bool,
+}
+
+impl OfferData {
+    /// Merges the data saved in the poll data. Data which was already set takes precedence.
+    pub fn load(&mut self, map: HashMap<TradeOfferId, Value>) {
+        for (tradeofferid, data) in map {
+            self.map.entry(tradeofferid).or_insert(data);
+        }
+        
+        self.is_loaded = true;
+        // The poll data is updated from the shared data before saving.
+        self.changed = true;
+    }
+    
+    /// Removes the data for an offer.
+    pub fn remove(&mut self, tradeofferid: TradeOfferId) -> Option<Value> {
+        let data = self.map.remove(&tradeofferid);
+        
+        self.changed = self.changed || data.is_some();
+        data
+    }
+}
@@ -0,0 +1,540 @@
+//! Stores for recording trade offers as they pass through the poller.
+
+use crate::SteamID;
+use crate::time::ServerTime;
+use crate::types::TradeOfferId;
+use crate::enums::TradeOfferState;
+use crate::response::TradeOffer;
+use crate::error::{AnyhowError, FileError};
+use crate::helpers::write_file_atomic;
+use std::fmt;
+use std::path::PathBuf;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+
+/// A change in the state of a stored offer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StateChange {
+    /// The state of the offer.
+    pub state: TradeOfferState,
+    /// When the state was observed.
+    pub time: ServerTime,
+}
+
+/// A snapshot of a trade offer along with the history of its states.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredOffer {
+    /// The most recent snapshot of the offer.
+    pub offer: TradeOffer,
+    /// The states of the offer in the order they were observed.
+    pub state_history: Vec<StateChange>,
+}
+
+impl StoredOffer {
+    /// Updates the snapshot of the offer. The state is added to the history if it changed.
+    fn update(&mut self, offer: &TradeOffer, time: ServerTime) {
+        let is_state_changed = self.state_history
+            .last()
+            .map(|change| change.state != offer.trade_offer_state)
+            .unwrap_or(true);
+        
+        if is_state_changed {
+            self.state_history.push(StateChange {
+                state: offer.trade_offer_state,
+                time,
+            });
+        }
+        
+        self.offer = offer.clone();
+    }
+}
+
+impl From<(&TradeOffer, ServerTime)> for StoredOffer {
+    fn from((offer, time): (&TradeOffer, ServerTime)) -> Self {
+        Self {
+            offer: offer.clone(),
+            state_history: vec![StateChange {
+                state: offer.trade_offer_state,
+                time,
+            }],
+        }
+    }
+}
+
+/// A query for stored offers. Every condition which is set must match. The default query matches 
+/// every offer.
+/// 
+/// # Examples
+/// ```
+/// use steam_tradeoffer_manager::SteamID;
+/// use steam_tradeoffer_manager::offer_store::OfferQuery;
+/// use steam_tradeoffer_manager::chrono::{Duration, Utc};
+/// 
+/// // Offers partner sent us in the past week.
+/// let query = OfferQuery {
+///     partner: Some(SteamID::from(76561198000000000)),
+///     is_our_offer: Some(false),
+///     created_after: Some(Utc::now() - Duration::try_weeks(1).unwrap()),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OfferQuery {
+    /// Offers with this partner.
+    pub partner: Option<SteamID>,
+    /// Offers currently in this state.
+    pub state: Option<TradeOfferState>,
+    /// Offers created by us or sent to us.
+    pub is_our_offer: Option<bool>,
+    /// Offers created at or after this time.
+    pub created_after: Option<ServerTime>,
+    /// Offers created before this time.
+    pub created_before: Option<ServerTime>,
+    /// Offers including an item with this `market_hash_name` on either side.
+    pub market_hash_name: Option<String>,
+}
+
+impl OfferQuery {
+    /// Whether the offer matches this query.
+    pub fn matches(&self, offer: &TradeOffer) -> bool {
+        if self.partner.is_some_and(|partner| partner != offer.partner) {
+            return false;
+        }
+        
+        if self.state.is_some_and(|state| state != offer.trade_offer_state) {
+            return false;
+        }
+        
+        if self.is_our_offer.is_some_and(|is_our_offer| is_our_offer != offer.is_our_offer) {
+            return false;
+        }
+        
+        if self.created_after.is_some_and(|date| offer.time_created < date) {
+            return false;
+        }
+        
+        if self.created_before.is_some_and(|date| offer.time_created >= date) {
+            return false;
+        }
+        
+        if let Some(market_hash_name) = &self.market_hash_name {
+            return offer.items_to_give
+                .iter()
+                .chain(offer.items_to_receive.iter())
+                .any(|asset| asset.classinfo.market_hash_name.as_ref() == Some(market_hash_name));
+        }
+        
+        true
+    }
+}
+
+/// Records snapshots of trade offers as they pass through the poller.
+/// 
+/// [`MemoryOfferStore`] and [`FileOfferStore`] are provided. Set using 
+/// [`TradeOfferManagerBuilder::offer_store`][crate::TradeOfferManagerBuilder::offer_store].
+#[async_trait]
+pub trait OfferStore: fmt::Debug + Send + Sync {
+    /// Records a snapshot of the offer for `steamid`, observed at `time`.
+    async fn record(
+        &self,
+        steamid: SteamID,
+        offer: &TradeOffer,
+        time: ServerTime,
+    ) -> Result<(), AnyhowError>;
+    
+    /// Records snapshots of several offers for `steamid`, observed at `time`. The poller records 
+    /// the offers from each poll using this. The default implementation calls 
+    /// [`OfferStore::record`] for each offer.
+    async fn record_many(
+        &self,
+        steamid: SteamID,
+        offers: &[&TradeOffer],
+        time: ServerTime,
+    ) -> Result<(), AnyhowError> {
+        for offer in offers {
+            self.record(steamid, offer, time).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Gets a stored offer for `steamid`.
+    async fn get(
+        &self,
+        steamid: SteamID,
+        tradeofferid: TradeOfferId,
+    ) -> Result<Option<StoredOffer>, AnyhowError>;
+    
+    /// Gets the stored offers for `steamid` matching the query, ordered by when they were 
+    /// created.
+    async fn query(
+        &self,
+        steamid: SteamID,
+        query: &OfferQuery,
+    ) -> Result<Vec<StoredOffer>, AnyhowError>;
+}
+
+type OfferMap = HashMap<TradeOfferId, StoredOffer>;
+
+/// The default number of offers kept for each account.
+const DEFAULT_MAX_OFFERS: usize = 10_000;
+
+/// Collects the offers matching the query ordered by when they were created.
+fn query_offers(offers: &OfferMap, query: &OfferQuery) -> Vec<StoredOffer> {
+    let mut offers = offers
+        .values()
+        .filter(|stored| query.matches(&stored.offer))
+        .cloned()
+        .collect::<Vec<_>>();
+    
+    offers.sort_by_key(|stored| (stored.offer.time_created, stored.offer.tradeofferid));
+    offers
+}
+
+/// Records an offer into the map.
+fn record_offer(offers: &mut OfferMap, offer: &TradeOffer, time: ServerTime) {
+    if let Some(stored) = offers.get_mut(&offer.tradeofferid) {
+        stored.update(offer, time);
+    } else {
+        offers.insert(offer.tradeofferid, StoredOffer::from((offer, time)));
+    }
+}
+
+/// Removes the offers which were updated least recently until at most `max_offers` remain.
+fn prune_offers(offers: &mut OfferMap, max_offers: usize) {
+    let Some(excess) = offers.len().checked_sub(max_offers).filter(|excess| *excess > 0) else {
+        return;
+    };
+    let mut oldest = offers
+        .values()
+        .map(|stored| (stored.offer.time_updated, stored.offer.tradeofferid))
+        .collect::<Vec<_>>();
+    
+    oldest.sort_unstable();
+    
+    for (_time_updated, tradeofferid) in oldest.into_iter().take(excess) {
+        offers.remove(&tradeofferid);
+    }
+}
+
+/// Stores offers in memory. Offers are lost when the store is dropped. Clones share the same 
+/// underlying data.
+#[derive(Debug, Clone)]
+pub struct MemoryOfferStore {
+    inner: Arc<Mutex<HashMap<u64, OfferMap>>>,
+    max_offers: usize,
+}
+
+impl Default for MemoryOfferStore {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(HashMap::new())),
+            max_offers: DEFAULT_MAX_OFFERS,
+        }
+    }
+}
+
+impl MemoryOfferStore {
+    /// Creates a new empty [`MemoryOfferStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// The maximum number of offers kept for each account. Offers which were updated least 
+    /// recently are removed first. Default is 10,000.
+    pub fn max_offers(mut self, max_offers: usize) -> Self {
+        self.max_offers = max_offers;
+        self
+    }
+}
+
+#[async_trait]
+impl OfferStore for MemoryOfferStore {
+    async fn record(
+        &self,
+        steamid: SteamID,
+        offer: &TradeOffer,
+        time: ServerTime,
+    ) -> Result<(), AnyhowError> {
+        self.record_many(steamid, &[offer], time).await
+    }
+    
+    async fn record_many(
+        &self,
+        steamid: SteamID,
+        offers: &[&TradeOffer],
+        time: ServerTime,
+    ) -> Result<(), AnyhowError> {
+        let mut inner = self.inner.lock().unwrap();
+        let stored_offers = inner.entry(u64::from(steamid)).or_default();
+        
+        for offer in offers {
+            record_offer(stored_offers, offer, time);
+        }
+        
+        prune_offers(stored_offers, self.max_offers);
+        Ok(())
+    }
+    
+    async fn get(
+        &self,
+        steamid: SteamID,
+        tradeofferid: TradeOfferId,
+    ) -> Result<Option<StoredOffer>, AnyhowError> {
+        Ok(self.inner.lock().unwrap()
+            .get(&u64::from(steamid))
+            .and_then(|offers| offers.get(&tradeofferid))
+            .cloned())
+    }
+    
+    async fn query(
+        &self,
+        steamid: SteamID,
+        query: &OfferQuery,
+    ) -> Result<Vec<StoredOffer>, AnyhowError> {
+        Ok(self.inner.lock().unwrap()
+            .get(&u64::from(steamid))
+            .map(|offers| query_offers(offers, query))
+            .unwrap_or_default())
+    }
+}
+
+/// Stores offers as `offers_<steamid>.json` files in a directory. Files are read once and kept 
+/// in memory. The file is rewritten once for each call to [`OfferStore::record_many`], which the 
+/// poller uses to record the offers from each poll. Clones share the same underlying data.
+#[derive(Debug, Clone)]
+pub struct FileOfferStore {
+    directory: PathBuf,
+    inner: Arc<tokio::sync::Mutex<HashMap<u64, OfferMap>>>,
+    max_offers: usize,
+}
+
+impl FileOfferStore {
+    /// Creates a new [`FileOfferStore`] storing files in `directory`.
+    pub fn new<T>(directory: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        Self {
+            directory: directory.into(),
+            inner: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            max_offers: DEFAULT_MAX_OFFERS,
+        }
+    }
+    
+    /// The maximum number of offers kept for each account. Offers which were updated least 
+    /// recently are removed first. Default is 10,000.
+    pub fn max_offers(mut self, max_offers: usize) -> Self {
+        self.max_offers = max_offers;
+        self
+    }
+    
+    fn filepath(&self, steamid: SteamID) -> PathBuf {
+        self.directory.join(format!("offers_{}.json", u64::from(steamid)))
+    }
+    
+    /// Reads the offers for `steamid` from file into `inner` if they are not already loaded.
+    async fn load<'a>(
+        &self,
+        inner: &'a mut HashMap<u64, OfferMap>,
+        steamid: SteamID,
+    ) -> Result<&'a mut OfferMap, AnyhowError> {
+        let offers = match inner.entry(u64::from(steamid)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let offers = match async_fs::read_to_string(self.filepath(steamid)).await {
+                    Ok(data) => serde_json::from_str::<Vec<StoredOffer>>(&data)
+                        .map_err(FileError::from)?
+                        .into_iter()
+                        .map(|stored| (stored.offer.tradeofferid, stored))
+                        .collect(),
+                    Err(error) if error.kind() == std::io::ErrorKind::NotFound => OfferMap::new(),
+                    Err(error) => return Err(FileError::from(error).into()),
+                };
+                
+                entry.insert(offers)
+            },
+        };
+        
+        Ok(offers)
+    }
+}
+
+#[async_trait]
+impl OfferStore for FileOfferStore {
+    async fn record(
+        &self,
+        steamid: SteamID,
+        offer: &TradeOffer,
+        time: ServerTime,
+    ) -> Result<(), AnyhowError> {
+        self.record_many(steamid, &[offer], time).await
+    }
+    
+    async fn record_many(
+        &self,
+        steamid: SteamID,
+        offers: &[&TradeOffer],
+        time: ServerTime,
+    ) -> Result<(), AnyhowError> {
+        if offers.is_empty() {
+            return Ok(());
+        }
+        
+        let mut inner = self.inner.lock().await;
+        let stored_offers = self.load(&mut inner, steamid).await?;
+        
+        for offer in offers {
+            record_offer(stored_offers, offer, time);
+        }
+        
+        prune_offers(stored_offers, self.max_offers);
+        
+        let data = serde_json::to_string(&stored_offers.values().collect::<Vec<_>>())
+            .map_err(FileError::from)?;
+        
+        write_file_atomic(self.filepath(steamid), data.as_bytes()).await
+            .map_err(FileError::from)?;
+        
+        Ok(())
+    }
+    
+    async fn get(
+        &self,
+        steamid: SteamID,
+        tradeofferid: TradeOfferId,
+    ) -> Result<Option<StoredOffer>, AnyhowError> {
+        let mut inner = self.inner.lock().await;
+        let offers = self.load(&mut inner, steamid).await?;
+        
+        Ok(offers.get(&tradeofferid).cloned())
+    }
+    
+    async fn query(
+        &self,
+        steamid: SteamID,
+        query: &OfferQuery,
+    ) -> Result<Vec<StoredOffer>, AnyhowError> {
+        let mut inner = self.inner.lock().await;
+        let offers = self.load(&mut inner, steamid).await?;
+        
+        Ok(query_offers(offers, query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    
+    #[tokio::test]
+    async fn records_state_history() {
+        let store = MemoryOfferStore::new();
+        let steamid = SteamID::from(76561198000000000);
+        let mut offer = TradeOffer {
+            tradeofferid: 1,
+            ..Default::default()
+        };
+        let now = crate::time::get_server_time_now();
+        
+        store.record(steamid, &offer, now).await.unwrap();
+        store.record(steamid, &offer, now).await.unwrap();
+        offer.trade_offer_state = TradeOfferState::Accepted;
+        store.record(steamid, &offer, now).await.unwrap();
+        
+        let stored = store.get(steamid, 1).await.unwrap().unwrap();
+        let states = stored.state_history
+            .iter()
+            .map(|change| change.state)
+            .collect::<Vec<_>>();
+        
+        assert_eq!(states, vec![TradeOfferState::Active, TradeOfferState::Accepted]);
+    }
+    
+    #[tokio::test]
+    async fn queries_by_partner_and_state() {
+        let store = MemoryOfferStore::new();
+        let steamid = SteamID::from(76561198000000000);
+        let partner = SteamID::from(76561198000000001);
+        let now = crate::time::get_server_time_now();
+        
+        for (tradeofferid, partner, trade_offer_state) in [
+            (1, partner, TradeOfferState::Active),
+            (2, partner, TradeOfferState::Accepted),
+            (3, SteamID::from(76561198000000002), TradeOfferState::Active),
+        ] {
+            let offer = TradeOffer {
+                tradeofferid,
+                partner,
+                trade_offer_state,
+                ..Default::default()
+            };
+            
+            store.record(steamid, &offer, now).await.unwrap();
+        }
+        
+        let offers = store.query(steamid, &OfferQuery {
+            partner: Some(partner),
+            state: Some(TradeOfferState::Active),
+            ..Default::default()
+        }).await.unwrap();
+        
+        assert_eq!(offers.len(), 1);
+        assert_eq!(offers[0].offer.tradeofferid, 1);
+    }
+    
+    #[tokio::test]
+    async fn prunes_least_recently_updated_offers() {
+        let store = MemoryOfferStore::new()
+            .max_offers(2);
+        let steamid = SteamID::from(76561198000000000);
+        let now = crate::time::get_server_time_now();
+        let offers = [3, 1, 2]
+            .map(|tradeofferid| TradeOffer {
+                tradeofferid,
+                time_updated: now + chrono::Duration::try_seconds(tradeofferid as i64).unwrap(),
+                ..Default::default()
+            });
+        
+        store.record_many(steamid, &offers.iter().collect::<Vec<_>>(), now).await.unwrap();
+        
+        let tradeofferids = store.query(steamid, &OfferQuery::default()).await.unwrap()
+            .into_iter()
+            .map(|stored| stored.offer.tradeofferid)
+            .collect::<HashSet<_>>();
+        
+        assert_eq!(tradeofferids, HashSet::from([2, 3]));
+    }
+    
+    #[tokio::test]
+    async fn file_store_records_many_offers() {
+        let directory = std::env::temp_dir()
+            .join(format!("offer_store_records_many_{}", std::process::id()));
+        let steamid = SteamID::from(76561198000000000);
+        let now = crate::time::get_server_time_now();
+        let offers = [1, 2, 3]
+            .map(|tradeofferid| TradeOffer {
+                tradeofferid,
+                ..Default::default()
+            });
+        
+        let _ = async_fs::remove_dir_all(&directory).await;
+        async_fs::create_dir_all(&directory).await.unwrap();
+        FileOfferStore::new(&directory)
+            .record_many(steamid, &offers.iter().collect::<Vec<_>>(), now)
+            .await
+            .unwrap();
+        
+        // Loaded from the file by a new store.
+        let stored = FileOfferStore::new(&directory)
+            .query(steamid, &OfferQuery::default())
+            .await
+            .unwrap();
+        
+        assert_eq!(stored.len(), 3);
+        let _ = async_fs::remove_dir_all(&directory).await;
+    }
+}
@@ -0,0 +1,180 @@
+use crate::api::response::RawTradeOffer;
+use crate::enums::TradeOfferState;
+use crate::time::ServerTime;
+use crate::types::TradeOfferId;
+use std::fmt;
+use chrono::Duration;
+
+/// Decides which offers sent by us are cancelled during a poll.
+/// 
+/// The policy is only consulted for offers sent by us which can be cancelled, i.e. offers in the 
+/// [`TradeOfferState::Active`] or [`TradeOfferState::CreatedNeedsConfirmation`] state that are 
+/// not already in progress.
+pub trait CancelPolicy: fmt::Debug + Send + Sync {
+    /// Whether the offer should be cancelled. `now` is the time the poll was started.
+    fn should_cancel(&self, offer: &RawTradeOffer, now: ServerTime) -> bool;
+}
+
+/// Cancels offers which have not been updated for longer than a duration. This is the policy 
+/// used when `cancel_duration` is set in [`PollOptions`][super::PollOptions].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CancelDurationPolicy {
+    /// The duration after an active offer has not been updated to cancel it. Active offers are 
+    /// not cancelled if this is not set.
+    pub active_duration: Option<Duration>,
+    /// The duration after an offer awaiting confirmation has not been updated to cancel it. 
+    /// Offers awaiting confirmation are not cancelled if this is not set.
+    pub needs_confirmation_duration: Option<Duration>,
+}
+
+impl CancelDurationPolicy {
+    /// Creates a new [`CancelDurationPolicy`] using the same duration for active offers and 
+    /// offers awaiting confirmation.
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            active_duration: Some(duration),
+            needs_confirmation_duration: Some(duration),
+        }
+    }
+}
+
+impl CancelPolicy for CancelDurationPolicy {
+    fn should_cancel(&self, offer: &RawTradeOffer, now: ServerTime) -> bool {
+        let duration = match offer.trade_offer_state {
+            TradeOfferState::Active => self.active_duration,
+            TradeOfferState::CreatedNeedsConfirmation => self.needs_confirmation_duration,
+            _ => None,
+        };
+        
+        if let Some(duration) = duration {
+            offer.time_updated < now - duration
+        } else {
+            false
+        }
+    }
+}
+
+/// The reason an offer sent by us was cancelled by the poller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CancelReason {
+    /// The offer was cancelled because of the `cancel_policy` or `cancel_duration` in 
+    /// [`PollOptions`][super::PollOptions].
+    Policy,
+    /// The offer was cancelled because the number of active offers sent by us exceeded the 
+    /// `cancel_offer_count` in [`PollOptions`][super::PollOptions].
+    OfferCount,
+}
+
+/// Selects the oldest offers to cancel so that no more than `count` offers remain. Offers which 
+/// have been updated within `min_age` of `now` are never selected.
+pub(crate) fn oldest_offers_over_count<'a, I>(
+    offers: I,
+    count: usize,
+    min_age: Duration,
+    now: ServerTime,
+) -> Vec<TradeOfferId>
+where
+    I: IntoIterator<Item = &'a RawTradeOffer>,
+{
+    let mut offers = offers.into_iter().collect::<Vec<_>>();
+    
+    if offers.len() <= count {
+        return Vec::new();
+    }
+    
+    let over_count = offers.len() - count;
+    
+    // Oldest first. The time the offer was updated reflects when it was confirmed, if necessary.
+    offers.sort_by_key(|offer| offer.time_updated);
+    offers
+        .into_iter()
+        .take(over_count)
+        // Offers are sorted so every offer after the first recent offer is also recent.
+        .take_while(|offer| offer.time_updated <= now - min_age)
+        .map(|offer| offer.tradeofferid)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::ConfirmationMethod;
+    
+    fn offer_updated_at(
+        trade_offer_state: TradeOfferState,
+        time_updated: ServerTime,
+    ) -> RawTradeOffer {
+        RawTradeOffer {
+            tradeofferid: 1,
+            tradeid: None,
+            accountid_other: 1,
+            message: None,
+            items_to_receive: Vec::new(),
+            items_to_give: Vec::new(),
+            is_our_offer: true,
+            from_real_time_trade: false,
+            expiration_time: time_updated,
+            time_created: time_updated,
+            time_updated,
+            trade_offer_state,
+            escrow_end_date: None,
+            confirmation_method: ConfirmationMethod::None,
+        }
+    }
+    
+    #[test]
+    fn cancels_offers_older_than_duration() {
+        let now = crate::time::get_server_time_now();
+        let policy = CancelDurationPolicy::new(Duration::try_minutes(10).unwrap());
+        let old = now - Duration::try_minutes(11).unwrap();
+        let recent = now - Duration::try_minutes(9).unwrap();
+        
+        assert!(policy.should_cancel(&offer_updated_at(TradeOfferState::Active, old), now));
+        assert!(policy.should_cancel(&offer_updated_at(TradeOfferState::CreatedNeedsConfirmation, old), now));
+        assert!(!policy.should_cancel(&offer_updated_at(TradeOfferState::Active, recent), now));
+    }
+    
+    #[test]
+    fn uses_separate_duration_for_offers_awaiting_confirmation() {
+        let now = crate::time::get_server_time_now();
+        let policy = CancelDurationPolicy {
+            active_duration: None,
+            needs_confirmation_duration: Some(Duration::try_minutes(10).unwrap()),
+        };
+        let old = now - Duration::try_minutes(11).unwrap();
+        
+        assert!(!policy.should_cancel(&offer_updated_at(TradeOfferState::Active, old), now));
+        assert!(policy.should_cancel(&offer_updated_at(TradeOfferState::CreatedNeedsConfirmation, old), now));
+    }
+    
+    #[test]
+    fn selects_oldest_offers_over_count() {
+        let now = crate::time::get_server_time_now();
+        let offers = (1..=5)
+            .map(|minutes| RawTradeOffer {
+                tradeofferid: minutes as u64,
+                ..offer_updated_at(TradeOfferState::Active, now - Duration::try_minutes(minutes).unwrap())
+            })
+            .collect::<Vec<_>>();
+        let mut selected = oldest_offers_over_count(&offers, 3, Duration::zero(), now);
+        
+        selected.sort();
+        
+        assert_eq!(selected, vec![4, 5]);
+        assert!(oldest_offers_over_count(&offers, 5, Duration::zero(), now).is_empty());
+    }
+    
+    #[test]
+    fn does_not_select_offers_under_min_age() {
+        let now = crate::time::get_server_time_now();
+        let offers = (1..=5)
+            .map(|minutes| RawTradeOffer {
+                tradeofferid: minutes as u64,
+                ..offer_updated_at(TradeOfferState::Active, now - Duration::try_minutes(minutes).unwrap())
+            })
+            .collect::<Vec<_>>();
+        let selected = oldest_offers_over_count(&offers, 1, Duration::try_minutes(4).unwrap(), now);
+        
+        assert_eq!(selected, vec![5, 4]);
+    }
+}
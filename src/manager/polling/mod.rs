@@ -1,56 +1,104 @@
 //! Models related to polling offers.
 
-mod file;
 mod poll_type;
 mod poll_action;
 mod poller;
 mod poll_data;
+mod poll_event;
+mod poll_data_store;
+mod poll_backoff;
+mod cancel_policy;
+mod poll_status;
+mod pending_escrow;
 
 pub use poll_type::PollType;
 pub use poll_action::PollAction;
-pub use poller::{Result, Poll};
+pub use poll_event::PollEvent;
 pub use poll_data::PollData;
-/// The receiver for polling events.
-pub type PollReceiver = mpsc::Receiver<Result>;
+pub use poll_data_store::{PollDataStore, FilePollDataStore, MemoryPollDataStore};
+pub use poll_backoff::PollBackoff;
+pub use cancel_policy::{CancelPolicy, CancelDurationPolicy, CancelReason};
+pub use poll_status::PollStatus;
+pub use pending_escrow::PendingEscrow;
+/// The receiver for polling events. Each receiver gets every event. A receiver that falls too far 
+/// behind will receive [`RecvError::Lagged`] with the number of skipped events rather than 
+/// blocking the poller.
+pub type PollReceiver = broadcast::Receiver<PollEvent>;
 /// The sender for polling events.
 pub type PollSender = mpsc::Sender<PollAction>;
+/// Error returned when receiving from a [`PollReceiver`]. Re-export from [`tokio`].
+pub use tokio::sync::broadcast::error::RecvError;
 
 use poller::Poller;
 
+use super::TradeOfferManager;
 use crate::SteamID;
-use crate::api::SteamTradeOfferAPI;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use chrono::{Duration, DateTime};
-use tokio::sync::{Mutex, mpsc};
+use tokio::sync::{broadcast, mpsc, Semaphore};
 use tokio::task::JoinHandle;
+use tokio::time::Instant;
 
 const DEFAULT_POLL_INTERVAL_SECONDS: i64 = 30;
 const DEFAULT_FULL_UPDATE_SECONDS: i64 = 5 * 60;
+const DEFAULT_MISSING_CLASSINFO_MAX_ATTEMPTS: u32 = 10;
 // Duration in milliseconds for when a poll was called too recently.
 const CALLED_TOO_RECENTLY_MILLISECONDS: i64 = 400;
+// How many events are buffered for each receiver before it starts lagging.
+const POLL_EVENT_CAPACITY: usize = 256;
 
 /// Options for polling.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct PollOptions {
     /// The duration after a sent offer has been active to cancel during a poll. Offers will 
-    /// not be cancelled if this is not set.
+    /// not be cancelled if this is not set. Ignored if `cancel_policy` is set.
     pub cancel_duration: Option<Duration>,
+    /// The policy used to decide which sent offers to cancel during a poll. Takes precedence 
+    /// over `cancel_duration`.
+    pub cancel_policy: Option<Arc<dyn CancelPolicy>>,
+    /// The maximum number of active offers sent by us. When exceeded during a poll, the oldest 
+    /// active sent offers are cancelled until the count is within this limit. Steam allows at 
+    /// most 30 active sent offers. Offers will not be cancelled if this is not set.
+    pub cancel_offer_count: Option<usize>,
+    /// The minimum duration since an offer was last updated before it can be cancelled by 
+    /// `cancel_offer_count`. Default is no minimum.
+    pub cancel_offer_count_min_age: Duration,
     /// The duration after the last poll becomes stale and a new one must be obtained when 
     /// polling using [`steam_tradeoffer_manager::polling::PollType::Auto`]. Default is 5 minutes.
     pub poll_full_update_duration: Duration,
     /// Interval to poll at. Default is 30 seconds.
     pub poll_interval: Duration,
+    /// Interval to poll at while there are active offers awaiting a response. Useful for 
+    /// responding to changes quickly while trading. Default is `None`, which uses `poll_interval`.
+    pub active_poll_interval: Option<Duration>,
+    /// Backoff applied to the poll interval after failed polls. Set to `None` to always poll at 
+    /// the regular interval. Default is [`PollBackoff::default`].
+    pub backoff: Option<PollBackoff>,
+    /// Whether to accept mobile confirmations for offers sent by the manager during polls. 
+    /// Requires an identity secret. Offers sent by other clients are not confirmed. Default is 
+    /// `false`.
+    pub confirm_sent_offers: bool,
+    /// The number of polls to try loading the descriptions for a changed offer before giving up 
+    /// on it. See [`PollEvent::OfferMissingClassInfo`]. Default is 10.
+    pub missing_classinfo_max_attempts: u32,
 }
 
 impl Default for PollOptions {
     fn default() -> Self {
         Self {
             cancel_duration: None,
+            cancel_policy: None,
+            cancel_offer_count: None,
+            cancel_offer_count_min_age: Duration::zero(),
             // unwrap is safe because the value is in range
             poll_full_update_duration: Duration::try_seconds(DEFAULT_FULL_UPDATE_SECONDS).unwrap(),
             // unwrap is safe because the value is in range
             poll_interval: Duration::try_seconds(DEFAULT_POLL_INTERVAL_SECONDS).unwrap(),
+            active_poll_interval: None,
+            backoff: Some(PollBackoff::default()),
+            confirm_sent_offers: false,
+            missing_classinfo_max_attempts: DEFAULT_MISSING_CLASSINFO_MAX_ATTEMPTS,
         }
     }
 }
@@ -63,25 +111,78 @@ impl PollOptions {
             ..Default::default()
         }
     }
+    
+    /// Gets the policy used for cancelling offers, if any.
+    fn get_cancel_policy(&self) -> Option<Arc<dyn CancelPolicy>> {
+        if let Some(cancel_policy) = &self.cancel_policy {
+            return Some(Arc::clone(cancel_policy));
+        }
+        
+        self.cancel_duration
+            .map(|duration| Arc::new(CancelDurationPolicy::new(duration)) as Arc<dyn CancelPolicy>)
+    }
+    
+    /// Gets the interval to wait before the next poll.
+    fn next_poll_interval(
+        &self,
+        consecutive_errors: u32,
+        has_active_offers: bool,
+    ) -> std::time::Duration {
+        let poll_interval = self.poll_interval.to_std()
+            .unwrap_or(std::time::Duration::from_secs(DEFAULT_POLL_INTERVAL_SECONDS as u64));
+        
+        if consecutive_errors > 0 {
+            if let Some(backoff) = &self.backoff {
+                return backoff.interval(poll_interval, consecutive_errors);
+            }
+            
+            return poll_interval;
+        }
+        
+        match self.active_poll_interval.and_then(|interval| interval.to_std().ok()) {
+            Some(active_poll_interval) if has_active_offers => active_poll_interval,
+            _ => poll_interval,
+        }
+    }
 }
 
-/// Packs the sender, receiver, and [`JoinHandle`] for the poller.
+/// Controls when polls are performed relative to other pollers.
+#[derive(Debug, Clone, Default)]
+pub struct PollSchedule {
+    /// How long to wait before the first poll.
+    pub start_delay: std::time::Duration,
+    /// Permits shared between pollers. A permit is held for the duration of each poll.
+    pub permits: Option<Arc<Semaphore>>,
+}
+
+/// Packs the sender, event sender, and [`JoinHandle`] for the poller. The polling task is 
+/// aborted when this is dropped.
+#[derive(Debug)]
 pub struct Polling {
     pub sender: mpsc::Sender<PollAction>,
-    pub receiver: mpsc::Receiver<Result>,
+    /// Used for creating new subscriptions. This is weak so that receivers are closed once the 
+    /// polling task ends.
+    pub events: broadcast::WeakSender<PollEvent>,
+    /// The status of the poller.
+    pub status: Arc<RwLock<PollStatus>>,
     pub handle: JoinHandle<()>,
 }
 
 impl Polling {
+    /// Spawns the polling task for the manager. Returns the [`Polling`] along with a receiver which is 
+    /// subscribed before any events are sent.
     pub fn new(
         steamid: SteamID,
-        api: SteamTradeOfferAPI,
+        manager: &TradeOfferManager,
         options: PollOptions,
-    ) -> Self {
-        let poll_data = file::load_poll_data(
-            steamid,
-            &api.data_directory,
-        ).unwrap_or_default();
+        schedule: PollSchedule,
+    ) -> (Self, PollReceiver) {
+        let api = Arc::clone(&manager.api);
+        let mobile_api = Arc::clone(&manager.mobile_api);
+        let store = Arc::clone(&manager.poll_data_store);
+        let awaiting_confirmation = Arc::clone(&manager.awaiting_confirmation);
+        let offer_store = manager.offer_store.clone();
+        let offer_data = Arc::clone(&manager.offer_data);
         // Allows sending a message into the poller.
         let (
             sender,
@@ -89,81 +190,202 @@ impl Polling {
         ) = mpsc::channel::<PollAction>(10);
         // Allows broadcasting polls outside of the poller.
         let (
-            polling_sender,
-            polling_receiver,
-        ) = mpsc::channel::<Result>(10);
+            events,
+            events_receiver,
+        ) = broadcast::channel::<PollEvent>(POLL_EVENT_CAPACITY);
+        let weak_events = events.downgrade();
+        let status = Arc::new(RwLock::new(PollStatus::default()));
+        let poller_status = Arc::clone(&status);
         let handle = tokio::spawn(async move {
-            // The asynchronous mutex allows only one poll to be performed at a time. This not only 
-            // ensures that the poller is not spammed with requests but also that the state is not 
-            // modified by multiple tasks at the same time.
-            let poller = Arc::new(Mutex::new(Poller {
+            let poll_data = match store.load(steamid).await {
+                Ok(poll_data) => poll_data.unwrap_or_default(),
+                Err(error) => {
+                    log::warn!("Error loading poll data: {error}");
+                    // This only fails if there are no receivers.
+                    let _ = events.send(PollEvent::PollDataLoadFailed(Arc::new(error)));
+                    PollData::default()
+                },
+            };
+            let mut poller = Poller {
                 api,
+                mobile_api,
+                confirm_sent_offers: false,
+                awaiting_confirmation,
                 steamid,
                 poll_data,
-                cancel_duration: options.cancel_duration,
+                store,
+                offer_store,
+                offer_data,
+                cancel_policy: None,
+                cancel_offer_count: None,
+                cancel_offer_count_min_age: Duration::zero(),
                 poll_full_update_duration: options.poll_full_update_duration,
-            }));
-            let handle = tokio::spawn(receive_poll_action_events(
-                receiver,
-                polling_sender.clone(),
-                poller.clone(),
-            ));
-            let poll_interval = options.poll_interval.to_std()
-                .unwrap_or(std::time::Duration::from_secs(DEFAULT_POLL_INTERVAL_SECONDS as u64));
+                poll_interval: options.poll_interval,
+                consecutive_errors: 0,
+                pending_offers: HashMap::new(),
+                missing_classinfo_max_attempts: options.missing_classinfo_max_attempts,
+                escrow_rechecks: HashMap::new(),
+                status: poller_status,
+            };
             
-            // Performs polls.
-            loop {
-                let poll = poller
-                    .lock().await
-                    .do_poll(PollType::Auto).await;
-                
-                match polling_sender.send(poll).await {
-                    Ok(_) => async_std::task::sleep(poll_interval).await,
-                    // The connection was closed or receiver stopped listening for events.
-                    Err(_error) => break,
-                }
-            }
+            poller.set_options(&options);
+            poller.load_offer_data();
+            poller.update_status(|status| {
+                status.last_full_update = poller.poll_data.last_poll_full_update;
+                status.tracked_offers = poller.poll_data.state_map.len();
+                status.pending_escrow = poller.pending_escrow();
+            });
             
-            handle.abort();
+            run(poller, receiver, events, options, schedule).await;
         });
-        
-        Self {
+        let polling = Self {
             sender,
-            receiver: polling_receiver,
+            events: weak_events,
+            status,
             handle,
-        }
+        };
+        
+        (polling, events_receiver)
+    }
+    
+    /// Subscribes to events from the poller. Returns `None` if the polling task has ended.
+    pub fn subscribe(&self) -> Option<PollReceiver> {
+        self.events.upgrade().map(|events| events.subscribe())
+    }
+    
+    /// Gets a snapshot of the status of the poller.
+    pub fn status(&self) -> PollStatus {
+        self.status.read()
+            .map(|status| status.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for Polling {
+    fn drop(&mut self) {
+        self.handle.abort();
     }
 }
 
-/// Receives poll action events.
-async fn receive_poll_action_events(
+/// Performs polls at the interval given by `options` and handles poll actions until polling is 
+/// stopped. The poll data is saved before returning. Only one poll is performed at a time. This 
+/// not only ensures that the poller is not spammed with requests but also that the state is not 
+/// modified by multiple polls at the same time.
+async fn run(
+    mut poller: Poller,
     mut receiver: mpsc::Receiver<PollAction>,
-    sender: mpsc::Sender<Result>,
-    poller: Arc<Mutex<Poller>>,
+    events: broadcast::Sender<PollEvent>,
+    mut options: PollOptions,
+    schedule: PollSchedule,
 ) {
     // To prevent spam.
     let mut poll_events: HashMap<PollType, DateTime<chrono::Utc>> = HashMap::new();
+    let mut last_poll = Instant::now();
+    let mut next_poll = last_poll + schedule.start_delay;
+    // Whether there are still senders for actions.
+    let mut is_receiving_actions = true;
+    // Whether polling at the interval is paused.
+    let mut is_paused = false;
     
-    while let Some(message) = receiver.recv().await {
-        match message {
-            PollAction::DoPoll(poll_type) => {
-                // This type of poll was called too recently.
-                if is_called_too_recently(&mut poll_events, poll_type) {
-                    // Ignore it.
-                    continue;
-                }
-                
-                let poll = poller.lock().await.do_poll(poll_type).await;
-                
-                if sender.send(poll).await.is_err() {
-                    // They closed the connection.
-                    break;
-                }
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep_until(next_poll), if !is_paused => {
+                send_events(&events, do_poll(&mut poller, &schedule, PollType::Auto).await);
+                last_poll = Instant::now();
+                next_poll = next_poll_at(&poller, &options, last_poll);
+            },
+            message = receiver.recv(), if is_receiving_actions => match message {
+                Some(PollAction::DoPoll(poll_type)) => {
+                    // This type of poll was called too recently.
+                    if is_called_too_recently(&mut poll_events, poll_type) {
+                        // Ignore it.
+                        continue;
+                    }
+                    
+                    send_events(&events, do_poll(&mut poller, &schedule, poll_type).await);
+                },
+                Some(PollAction::SetOptions(new_options)) => {
+                    poller.set_options(&new_options);
+                    options = new_options;
+                    // Reschedule the next poll using the new interval.
+                    next_poll = next_poll_at(&poller, &options, last_poll);
+                },
+                Some(PollAction::Pause) => {
+                    is_paused = true;
+                    poller.update_status(|status| status.is_paused = true);
+                },
+                Some(PollAction::Resume) => if is_paused {
+                    is_paused = false;
+                    poller.update_status(|status| status.is_paused = false);
+                    // Poll right away since changes may have been missed while paused.
+                    next_poll = Instant::now();
+                },
+                // Breaks out of the loop and ends the task.
+                Some(PollAction::StopPolling) => break,
+                // All senders were dropped. Polls will continue to be performed at the interval.
+                None => {
+                    is_receiving_actions = false;
+                    
+                    // Nothing can resume polling anymore.
+                    if is_paused {
+                        break;
+                    }
+                },
             },
-            // Breaks out of the loop and ends the task.
-            PollAction::StopPolling => break,
         }
     }
+    
+    // Flush the poll data before the task ends.
+    poller.save_poll_data().await;
+}
+
+/// Gets when to perform the next poll after `last_poll`.
+fn next_poll_at(
+    poller: &Poller,
+    options: &PollOptions,
+    last_poll: Instant,
+) -> Instant {
+    let next_poll = last_poll + options.next_poll_interval(
+        poller.consecutive_errors,
+        poller.has_active_offers(),
+    );
+    
+    // Backing off from failed polls takes precedence over escrow.
+    if poller.consecutive_errors > 0 {
+        return next_poll;
+    }
+    
+    // Poll when the next offer is released from escrow if that is sooner.
+    match poller.next_escrow_end() {
+        Some(escrow_end) => next_poll.min(escrow_end),
+        None => next_poll,
+    }
+}
+
+/// Performs a poll while holding a permit from the schedule, if any.
+async fn do_poll(
+    poller: &mut Poller,
+    schedule: &PollSchedule,
+    poll_type: PollType,
+) -> Vec<PollEvent> {
+    // This only fails if the semaphore is closed, in which case the poll is performed anyway.
+    let _permit = match &schedule.permits {
+        Some(permits) => permits.acquire().await.ok(),
+        None => None,
+    };
+    
+    poller.do_poll(poll_type).await
+}
+
+/// Broadcasts the events from a poll in order. Events are dropped if there are no receivers.
+fn send_events(
+    sender: &broadcast::Sender<PollEvent>,
+    events: Vec<PollEvent>,
+) {
+    for event in events {
+        // This only fails if there are no receivers.
+        let _ = sender.send(event);
+    }
 }
 
 /// Checks if a poll was called too recently. Mutates the `poll_events` map to update the last 
@@ -184,4 +406,697 @@ fn is_called_too_recently(
         poll_events.insert(poll_type, chrono::Utc::now());
         false
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::TradeOfferState;
+    use crate::request::NewTradeOffer;
+    use crate::api::response::RawAsset;
+    use crate::time::ServerTime;
+    use crate::types::TradeOfferId;
+    use crate::test_util::{FakeSteam, FakeRoute, FakeFailure, fake_classinfo};
+    
+    const STEAMID: u64 = 76561198000000001;
+    const PARTNER: u64 = 76561198000000002;
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+    
+    /// Options which only poll when requested after the first poll.
+    fn options() -> PollOptions {
+        PollOptions {
+            poll_interval: Duration::try_hours(1).unwrap(),
+            ..PollOptions::default()
+        }
+    }
+    
+    /// Receives the events of the next poll.
+    async fn recv_poll(receiver: &mut PollReceiver) -> Vec<PollEvent> {
+        tokio::time::timeout(TIMEOUT, async {
+            let mut events = Vec::new();
+            
+            loop {
+                let event = receiver.recv().await.unwrap();
+                let is_end_of_poll = event.is_end_of_poll();
+                
+                events.push(event);
+                
+                if is_end_of_poll {
+                    return events;
+                }
+            }
+        }).await.unwrap()
+    }
+    
+    /// Requests a full update and receives its events.
+    async fn poll(
+        sender: &PollSender,
+        receiver: &mut PollReceiver,
+    ) -> Vec<PollEvent> {
+        poll_type(sender, receiver, PollType::FullUpdate).await
+    }
+    
+    /// Requests a poll of `poll_type` and receives its events.
+    async fn poll_type(
+        sender: &PollSender,
+        receiver: &mut PollReceiver,
+        poll_type: PollType,
+    ) -> Vec<PollEvent> {
+        // Polls of the same type requested too recently are ignored.
+        tokio::time::sleep(std::time::Duration::from_millis(CALLED_TOO_RECENTLY_MILLISECONDS as u64 + 50)).await;
+        sender.send(PollAction::DoPoll(poll_type)).await.unwrap();
+        recv_poll(receiver).await
+    }
+    
+    /// An item whose classinfo Steam does not have.
+    fn unknown_asset() -> RawAsset {
+        RawAsset {
+            appid: 440,
+            contextid: 2,
+            assetid: 1,
+            amount: 1,
+            missing: false,
+            classid: 999,
+            instanceid: None,
+        }
+    }
+    
+    /// Gets the attempts and whether the poller gave up from the offer missing classinfo event.
+    fn missing_classinfo_attempts(events: &[PollEvent]) -> Option<(u32, bool)> {
+        events.iter().find_map(|event| match event {
+            PollEvent::OfferMissingClassInfo { attempts, gave_up, .. } => Some((*attempts, *gave_up)),
+            _ => None,
+        })
+    }
+    
+    /// Waits until `polls` polls have been performed.
+    async fn wait_for_polls(
+        manager: &TradeOfferManager,
+        polls: u64,
+    ) {
+        tokio::time::timeout(TIMEOUT, async {
+            while manager.poll_status().map(|status| status.polls) != Some(polls) {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn classifies_offer_events() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let (received_tradeofferid, asset) = {
+            let mut state = steam.state();
+            let received_asset = state.add_item(partner, 440, 2, fake_classinfo(101, "Key"));
+            
+            (
+                state.receive_offer(partner, Vec::new(), vec![received_asset]),
+                state.add_item(steamid, 440, 2, fake_classinfo(102, "Hat")),
+            )
+        };
+        let manager = steam.manager_builder().build();
+        let sent_offer = manager.send_offer(&NewTradeOffer::builder(partner)
+            .items_to_give(vec![asset])
+            .build()).await.unwrap();
+        let (sender, mut receiver) = manager.start_polling(options()).unwrap();
+        let events = recv_poll(&mut receiver).await;
+        
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            PollEvent::NewReceivedOffer(offer) if offer.tradeofferid == received_tradeofferid,
+        )));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            PollEvent::NewSentOffer(offer) if offer.tradeofferid == sent_offer.tradeofferid,
+        )));
+        assert!(matches!(events.last(), Some(PollEvent::PollComplete)));
+        
+        // Steam only has second precision.
+        let escrow_end_date = crate::time::timestamp_to_server_time(
+            (crate::time::get_server_time_now() + Duration::try_days(15).unwrap()).timestamp(),
+        );
+        
+        {
+            let mut state = steam.state();
+            let mut received_offer = state.offer(received_tradeofferid).unwrap().clone();
+            
+            received_offer.trade_offer_state = TradeOfferState::InEscrow;
+            received_offer.escrow_end_date = Some(escrow_end_date);
+            received_offer.time_updated = crate::time::get_server_time_now();
+            state.insert_offer(received_offer);
+            state.set_offer_state(sent_offer.tradeofferid, TradeOfferState::Declined);
+        }
+        
+        let events = poll(&sender, &mut receiver).await;
+        
+        assert_eq!(events.len(), 4);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            PollEvent::ReceivedOfferChanged {
+                offer,
+                old_state: TradeOfferState::Active,
+            } if offer.tradeofferid == received_tradeofferid,
+        )));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            PollEvent::SentOfferChanged {
+                offer,
+                old_state: TradeOfferState::Active,
+            } if offer.trade_offer_state == TradeOfferState::Declined,
+        )));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            PollEvent::OfferEnteredEscrow {
+                tradeofferid,
+                escrow_end_date: date,
+            } if *tradeofferid == received_tradeofferid && *date == escrow_end_date,
+        )));
+        
+        // Offers which have not changed are not emitted again.
+        let events = poll(&sender, &mut receiver).await;
+        
+        assert_eq!(events.len(), 1);
+        manager.stop_polling();
+    }
+    
+    #[tokio::test]
+    async fn broadcasts_events_to_every_subscriber() {
+        let steamid = SteamID::from(STEAMID);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let manager = steam.manager_builder().build();
+        
+        assert!(manager.subscribe_polls().is_none());
+        
+        let (sender, mut receiver) = manager.start_polling(options()).unwrap();
+        
+        recv_poll(&mut receiver).await;
+        
+        let mut subscriber = manager.subscribe_polls().unwrap();
+        
+        assert_eq!(poll(&sender, &mut receiver).await.len(), 1);
+        assert_eq!(recv_poll(&mut subscriber).await.len(), 1);
+        
+        // Polling continues without any receivers.
+        drop(receiver);
+        drop(subscriber);
+        sender.send(PollAction::DoPoll(PollType::NewOffers)).await.unwrap();
+        wait_for_polls(&manager, 3).await;
+        
+        let mut subscriber = manager.subscribe_polls().unwrap();
+        
+        assert_eq!(poll(&sender, &mut subscriber).await.len(), 1);
+        assert_eq!(steam.state().request_count(FakeRoute::GetTradeOffers), 4);
+        manager.stop_polling();
+    }
+    
+    #[tokio::test]
+    async fn lagging_subscriber_skips_events() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let offer_count = POLL_EVENT_CAPACITY + 44;
+        
+        {
+            let mut state = steam.state();
+            
+            for _ in 0..offer_count {
+                let asset = state.add_item(partner, 440, 2, fake_classinfo(101, "Key"));
+                
+                state.receive_offer(partner, Vec::new(), vec![asset]);
+            }
+        }
+        
+        let manager = steam.manager_builder().build();
+        let (_sender, mut receiver) = manager.start_polling(options()).unwrap();
+        
+        wait_for_polls(&manager, 1).await;
+        
+        // One event for each offer followed by the end of the poll.
+        let skipped = offer_count + 1 - POLL_EVENT_CAPACITY;
+        
+        assert!(matches!(receiver.recv().await, Err(RecvError::Lagged(count)) if count == skipped as u64));
+        
+        let events = recv_poll(&mut receiver).await;
+        
+        assert_eq!(events.len(), POLL_EVENT_CAPACITY);
+        assert!(matches!(events.last(), Some(PollEvent::PollComplete)));
+        manager.stop_polling();
+    }
+    
+    #[tokio::test]
+    async fn pauses_and_resumes_polling() {
+        let steamid = SteamID::from(STEAMID);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let manager = steam.manager_builder().build();
+        let (sender, mut receiver) = manager.start_polling(PollOptions {
+            poll_interval: Duration::try_milliseconds(50).unwrap(),
+            ..PollOptions::default()
+        }).unwrap();
+        let poll_count = || steam.state().request_count(FakeRoute::GetTradeOffers);
+        
+        recv_poll(&mut receiver).await;
+        sender.send(PollAction::Pause).await.unwrap();
+        // Allow a poll in progress to finish.
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        
+        let paused_poll_count = poll_count();
+        
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        
+        assert_eq!(poll_count(), paused_poll_count);
+        assert!(manager.poll_status().unwrap().is_paused);
+        
+        // Requested polls are still performed while paused.
+        let mut receiver = manager.subscribe_polls().unwrap();
+        
+        poll(&sender, &mut receiver).await;
+        
+        assert_eq!(poll_count(), paused_poll_count + 1);
+        
+        sender.send(PollAction::Resume).await.unwrap();
+        recv_poll(&mut receiver).await;
+        
+        assert!(!manager.poll_status().unwrap().is_paused);
+        assert!(poll_count() > paused_poll_count + 1);
+        manager.stop_polling();
+    }
+    
+    #[tokio::test]
+    async fn set_options_reschedules_polls() {
+        let steamid = SteamID::from(STEAMID);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let manager = steam.manager_builder().build();
+        let (sender, mut receiver) = manager.start_polling(options()).unwrap();
+        
+        recv_poll(&mut receiver).await;
+        sender.send(PollAction::SetOptions(PollOptions {
+            poll_interval: Duration::try_milliseconds(50).unwrap(),
+            ..PollOptions::default()
+        })).await.unwrap();
+        
+        // Polls at the new interval without being requested.
+        recv_poll(&mut receiver).await;
+        recv_poll(&mut receiver).await;
+        
+        assert!(manager.poll_status().unwrap().polls >= 3);
+        manager.stop_polling();
+    }
+    
+    #[tokio::test]
+    async fn reports_poll_status() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        
+        {
+            let mut state = steam.state();
+            let asset = state.add_item(partner, 440, 2, fake_classinfo(101, "Key"));
+            
+            state.receive_offer(partner, Vec::new(), vec![asset]);
+        }
+        
+        let manager = steam.manager_builder().build();
+        
+        assert!(manager.poll_status().is_none());
+        
+        let (sender, mut receiver) = manager.start_polling(options()).unwrap();
+        
+        recv_poll(&mut receiver).await;
+        
+        let status = manager.poll_status().unwrap();
+        
+        assert_eq!(status.polls, 1);
+        assert_eq!(status.tracked_offers, 1);
+        assert_eq!(status.consecutive_errors, 0);
+        assert_eq!(status.last_full_update_succeeded, Some(true));
+        assert!(status.poll_started.is_none());
+        assert!(status.last_successful_poll.is_some());
+        assert!(status.last_full_update.is_some());
+        
+        steam.state().fail_next(FakeRoute::GetTradeOffers, FakeFailure::Status(500));
+        
+        let events = poll(&sender, &mut receiver).await;
+        
+        assert!(matches!(events.as_slice(), [PollEvent::PollFailed(_)]));
+        
+        let failed_status = manager.poll_status().unwrap();
+        
+        assert_eq!(failed_status.polls, 2);
+        assert_eq!(failed_status.failed_polls, 1);
+        assert_eq!(failed_status.consecutive_errors, 1);
+        assert_eq!(failed_status.last_full_update_succeeded, Some(false));
+        assert_eq!(failed_status.last_successful_poll, status.last_successful_poll);
+        
+        assert!(manager.stop_polling_gracefully(Duration::try_seconds(5).unwrap()).await);
+        assert!(manager.poll_status().is_none());
+    }
+    
+    #[tokio::test]
+    async fn gives_up_on_offers_missing_classinfo() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        
+        steam.state().receive_offer(partner, Vec::new(), vec![unknown_asset()]);
+        
+        let manager = steam.manager_builder().build();
+        let (sender, mut receiver) = manager.start_polling(PollOptions {
+            missing_classinfo_max_attempts: 2,
+            ..options()
+        }).unwrap();
+        let events = recv_poll(&mut receiver).await;
+        
+        assert_eq!(missing_classinfo_attempts(&events), Some((1, false)));
+        assert_eq!(manager.poll_status().unwrap().pending_offers, 1);
+        
+        let events = poll(&sender, &mut receiver).await;
+        
+        assert_eq!(missing_classinfo_attempts(&events), Some((2, true)));
+        assert_eq!(manager.poll_status().unwrap().pending_offers, 0);
+        
+        // The offer is not tried again until its state changes.
+        let events = poll(&sender, &mut receiver).await;
+        
+        assert_eq!(events.len(), 1);
+        manager.stop_polling();
+    }
+    
+    #[tokio::test]
+    async fn retries_pending_offers_on_polls_with_descriptions() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let tradeofferid = steam.state().receive_offer(partner, Vec::new(), vec![unknown_asset()]);
+        let manager = steam.manager_builder().build();
+        let (sender, mut receiver) = manager.start_polling(options()).unwrap();
+        let events = recv_poll(&mut receiver).await;
+        
+        assert_eq!(missing_classinfo_attempts(&events), Some((1, false)));
+        
+        {
+            let mut state = steam.state();
+            
+            state.add_classinfo(440, fake_classinfo(999, "Key"));
+            // The offer is no longer included in polls for active offers.
+            state.set_offer_state(tradeofferid, TradeOfferState::Declined);
+        }
+        
+        let events = poll_type(&sender, &mut receiver, PollType::NewOffers).await;
+        
+        assert!(events.iter().any(|event| matches!(
+            event,
+            PollEvent::NewReceivedOffer(offer) if offer.tradeofferid == tradeofferid,
+        )));
+        assert_eq!(manager.poll_status().unwrap().pending_offers, 0);
+        manager.stop_polling();
+    }
+    
+    /// Never finishes saving once `hang` is set.
+    #[derive(Debug, Default)]
+    struct HangingPollDataStore {
+        hang: Arc<std::sync::atomic::AtomicBool>,
+    }
+    
+    #[async_trait::async_trait]
+    impl PollDataStore for HangingPollDataStore {
+        async fn load(
+            &self,
+            _steamid: SteamID,
+        ) -> Result<Option<PollData>, crate::error::AnyhowError> {
+            Ok(None)
+        }
+        
+        async fn save(
+            &self,
+            _steamid: SteamID,
+            _poll_data: &PollData,
+        ) -> Result<(), crate::error::AnyhowError> {
+            if self.hang.load(std::sync::atomic::Ordering::SeqCst) {
+                std::future::pending::<()>().await;
+            }
+            
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn aborts_polling_when_graceful_stop_times_out() {
+        let steam = FakeSteam::start(SteamID::from(STEAMID)).unwrap();
+        let store = HangingPollDataStore::default();
+        let hang = Arc::clone(&store.hang);
+        let manager = steam.manager_builder()
+            .poll_data_store(store)
+            .build();
+        let (_sender, mut receiver) = manager.start_polling(options()).unwrap();
+        
+        recv_poll(&mut receiver).await;
+        hang.store(true, std::sync::atomic::Ordering::SeqCst);
+        
+        assert!(!manager.stop_polling_gracefully(Duration::try_milliseconds(100).unwrap()).await);
+        assert!(manager.poll_status().is_none());
+        // The receiver is closed once the aborted task is dropped.
+        assert!(matches!(
+            tokio::time::timeout(TIMEOUT, receiver.recv()).await.unwrap(),
+            Err(RecvError::Closed),
+        ));
+    }
+    
+    /// Puts an offer received from `partner` in escrow until `escrow_end_date`.
+    fn receive_escrowed_offer(
+        steam: &FakeSteam,
+        partner: SteamID,
+        escrow_end_date: ServerTime,
+    ) -> TradeOfferId {
+        let mut state = steam.state();
+        let asset = state.add_item(partner, 440, 2, fake_classinfo(101, "Key"));
+        let tradeofferid = state.receive_offer(partner, Vec::new(), vec![asset]);
+        let mut offer = state.offer(tradeofferid).unwrap().clone();
+        
+        offer.trade_offer_state = TradeOfferState::InEscrow;
+        offer.escrow_end_date = Some(escrow_end_date);
+        state.insert_offer(offer);
+        tradeofferid
+    }
+    
+    #[tokio::test]
+    async fn checks_unreleased_escrow_again_after_poll_interval() {
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(SteamID::from(STEAMID)).unwrap();
+        let escrow_end_date = crate::time::get_server_time_now() - Duration::try_minutes(1).unwrap();
+        
+        // One offer is still in escrow and checking the other fails.
+        receive_escrowed_offer(&steam, partner, escrow_end_date);
+        receive_escrowed_offer(&steam, partner, escrow_end_date);
+        steam.state().fail_next(FakeRoute::GetTradeOffer, FakeFailure::Status(500));
+        
+        let manager = steam.manager_builder().build();
+        let (_sender, mut receiver) = manager.start_polling(options()).unwrap();
+        let events = recv_poll(&mut receiver).await;
+        
+        assert!(!events.iter().any(|event| matches!(event, PollEvent::EscrowReleased { .. })));
+        
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        
+        let state = steam.state();
+        
+        assert_eq!(state.request_count(FakeRoute::GetTradeOffers), 1);
+        assert_eq!(state.request_count(FakeRoute::GetTradeOffer), 2);
+        drop(state);
+        assert_eq!(manager.pending_escrow().unwrap().len(), 2);
+        manager.stop_polling();
+    }
+    
+    #[tokio::test]
+    async fn releases_escrow_when_polled_offer_left_escrow() {
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(SteamID::from(STEAMID)).unwrap();
+        // Steam only has second precision.
+        let escrow_end_date = crate::time::timestamp_to_server_time(
+            (crate::time::get_server_time_now() + Duration::try_days(15).unwrap()).timestamp(),
+        );
+        let tradeofferid = receive_escrowed_offer(&steam, partner, escrow_end_date);
+        let manager = steam.manager_builder().build();
+        let (sender, mut receiver) = manager.start_polling(options()).unwrap();
+        
+        recv_poll(&mut receiver).await;
+        assert_eq!(manager.pending_escrow().unwrap().len(), 1);
+        steam.state().set_offer_state(tradeofferid, TradeOfferState::Accepted);
+        
+        let events = poll(&sender, &mut receiver).await;
+        
+        assert!(events.iter().any(|event| matches!(
+            event,
+            PollEvent::EscrowReleased {
+                tradeofferid: released_tradeofferid,
+                escrow_end_date: date,
+                trade_offer_state: TradeOfferState::Accepted,
+            } if *released_tradeofferid == tradeofferid && *date == escrow_end_date,
+        )));
+        assert!(manager.pending_escrow().unwrap().is_empty());
+        manager.stop_polling();
+    }
+    
+    #[tokio::test]
+    async fn aborts_polling_once_every_clone_is_dropped() {
+        let steam = FakeSteam::start(SteamID::from(STEAMID)).unwrap();
+        let manager = steam.manager_builder().build();
+        let clone = manager.clone();
+        let (_sender, mut receiver) = manager.start_polling(options()).unwrap();
+        
+        recv_poll(&mut receiver).await;
+        drop(manager);
+        assert!(clone.poll_status().is_some());
+        
+        drop(clone);
+        assert!(matches!(
+            tokio::time::timeout(TIMEOUT, receiver.recv()).await.unwrap(),
+            Err(RecvError::Closed),
+        ));
+    }
+    
+    #[tokio::test]
+    async fn persists_offer_data_across_restarts() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let store = MemoryPollDataStore::new();
+        let (tradeofferid, other_tradeofferid) = {
+            let mut state = steam.state();
+            let asset = state.add_item(partner, 440, 2, fake_classinfo(101, "Key"));
+            let other_asset = state.add_item(partner, 440, 2, fake_classinfo(102, "Hat"));
+            
+            (
+                state.receive_offer(partner, Vec::new(), vec![asset]),
+                state.receive_offer(partner, Vec::new(), vec![other_asset]),
+            )
+        };
+        let manager = steam.manager_builder()
+            .poll_data_store(store.clone())
+            .build();
+        
+        // Saved right away when not polling.
+        manager.set_offer_data(tradeofferid, serde_json::json!({ "order": 1 })).await;
+        
+        let (_sender, mut receiver) = manager.start_polling(options()).unwrap();
+        
+        recv_poll(&mut receiver).await;
+        // Saved along with the poll data while polling.
+        manager.set_offer_data(other_tradeofferid, serde_json::json!({ "order": 2 })).await;
+        assert!(manager.stop_polling_gracefully(Duration::try_seconds(5).unwrap()).await);
+        
+        let manager = steam.manager_builder()
+            .poll_data_store(store.clone())
+            .build();
+        
+        assert_eq!(manager.get_offer_data(tradeofferid).await, Some(serde_json::json!({ "order": 1 })));
+        assert_eq!(manager.get_offer_data(other_tradeofferid).await, Some(serde_json::json!({ "order": 2 })));
+        
+        steam.state().set_offer_state(tradeofferid, TradeOfferState::Accepted);
+        
+        let (_sender, mut receiver) = manager.start_polling(options()).unwrap();
+        let events = recv_poll(&mut receiver).await;
+        
+        assert!(events.iter().any(|event| matches!(
+            event,
+            PollEvent::ReceivedOfferChanged { offer, .. } if offer.tradeofferid == tradeofferid && offer.data.is_some(),
+        )));
+        // Data is removed once the offer reaches a final state.
+        assert_eq!(manager.get_offer_data(tradeofferid).await, None);
+        assert!(manager.stop_polling_gracefully(Duration::try_seconds(5).unwrap()).await);
+        
+        let offer_data = store.get(steamid).unwrap().offer_data;
+        
+        assert!(!offer_data.contains_key(&tradeofferid));
+        assert!(offer_data.contains_key(&other_tradeofferid));
+    }
+    
+    /// Marks the items in an offer as missing.
+    fn set_items_missing(
+        steam: &FakeSteam,
+        tradeofferid: TradeOfferId,
+    ) {
+        let mut state = steam.state();
+        let mut offer = state.offer(tradeofferid).unwrap().clone();
+        
+        for asset in offer.items_to_give.iter_mut().chain(offer.items_to_receive.iter_mut()) {
+            asset.missing = true;
+        }
+        
+        state.insert_offer(offer);
+    }
+    
+    #[tokio::test]
+    async fn reports_missing_items_once_across_restarts() {
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(SteamID::from(STEAMID)).unwrap();
+        let store = MemoryPollDataStore::new();
+        let tradeofferid = {
+            let mut state = steam.state();
+            let asset = state.add_item(partner, 440, 2, fake_classinfo(101, "Key"));
+            
+            state.receive_offer(partner, Vec::new(), vec![asset])
+        };
+        let manager = steam.manager_builder()
+            .poll_data_store(store.clone())
+            .build();
+        let (sender, mut receiver) = manager.start_polling(options()).unwrap();
+        let is_items_missing = |event: &PollEvent| matches!(
+            event,
+            PollEvent::OfferItemsMissing { offer, items } if offer.tradeofferid == tradeofferid && items.len() == 1,
+        );
+        
+        recv_poll(&mut receiver).await;
+        set_items_missing(&steam, tradeofferid);
+        
+        let events = poll(&sender, &mut receiver).await;
+        
+        assert_eq!(events.iter().filter(|event| is_items_missing(event)).count(), 1);
+        
+        let events = poll(&sender, &mut receiver).await;
+        
+        assert!(!events.iter().any(is_items_missing));
+        assert!(manager.stop_polling_gracefully(Duration::try_seconds(5).unwrap()).await);
+        
+        let manager = steam.manager_builder()
+            .poll_data_store(store)
+            .build();
+        let (_sender, mut receiver) = manager.start_polling(options()).unwrap();
+        let events = recv_poll(&mut receiver).await;
+        
+        assert!(!events.iter().any(is_items_missing));
+        manager.stop_polling();
+    }
+    
+    #[tokio::test]
+    async fn does_not_report_missing_items_for_cancelled_offers() {
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(SteamID::from(STEAMID)).unwrap();
+        let tradeofferid = {
+            let mut state = steam.state();
+            let asset = state.add_item(partner, 440, 2, fake_classinfo(101, "Key"));
+            let tradeofferid = state.receive_offer(partner, Vec::new(), vec![asset]);
+            let mut offer = state.offer(tradeofferid).unwrap().clone();
+            
+            // An old offer sent by us.
+            offer.is_our_offer = true;
+            offer.time_updated -= Duration::try_hours(1).unwrap();
+            state.insert_offer(offer);
+            tradeofferid
+        };
+        
+        set_items_missing(&steam, tradeofferid);
+        
+        let manager = steam.manager_builder().build();
+        let (_sender, mut receiver) = manager.start_polling(PollOptions {
+            cancel_duration: Some(Duration::try_minutes(10).unwrap()),
+            ..options()
+        }).unwrap();
+        let events = recv_poll(&mut receiver).await;
+        
+        assert!(events.iter().any(|event| matches!(event, PollEvent::AutoCancelled { .. })));
+        assert!(!events.iter().any(|event| matches!(event, PollEvent::OfferItemsMissing { .. })));
+        manager.stop_polling();
+    }
+}
@@ -0,0 +1,12 @@
+use crate::time::ServerTime;
+use crate::types::TradeOfferId;
+
+/// An offer in [`TradeOfferState::InEscrow`][crate::enums::TradeOfferState::InEscrow] tracked by 
+/// the poller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingEscrow {
+    /// The ID of the offer.
+    pub tradeofferid: TradeOfferId,
+    /// When the items in the offer are released from escrow.
+    pub escrow_end_date: ServerTime,
+}
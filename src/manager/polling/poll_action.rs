@@ -1,10 +1,18 @@
-use super::PollType;
+use super::{PollType, PollOptions};
 
 /// An action to send to the polling task.
 #[derive(Debug, Clone)]
 pub enum PollAction {
-    /// Perform a poll.
+    /// Perform a poll. Polls can be performed while polling is paused.
     DoPoll(PollType),
+    /// Replace the options used for polling. The next poll is rescheduled using the new 
+    /// interval. Poll data and in-flight state are kept.
+    SetOptions(PollOptions),
+    /// Pause polling at the interval. Polling stops if all senders are dropped while paused.
+    Pause,
+    /// Resume polling at the interval after [`PollAction::Pause`]. A poll is performed right 
+    /// away.
+    Resume,
     /// Stop polling.
     StopPolling,
 }
\ No newline at end of file
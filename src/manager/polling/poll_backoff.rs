@@ -0,0 +1,94 @@
+use chrono::Duration;
+
+const DEFAULT_MULTIPLIER: f64 = 2.0;
+const DEFAULT_MAX_INTERVAL_SECONDS: i64 = 10 * 60;
+const DEFAULT_JITTER: f64 = 0.1;
+
+/// Exponential backoff applied to the poll interval after failed polls. The interval is 
+/// multiplied by `multiplier` for each consecutive failed poll up to `max_interval`. The interval 
+/// resets once a poll succeeds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PollBackoff {
+    /// The factor the interval is multiplied by for each consecutive failed poll. Default is `2`.
+    pub multiplier: f64,
+    /// The maximum interval to wait between polls. Default is 10 minutes.
+    pub max_interval: Duration,
+    /// Random jitter applied to the interval as a fraction of the interval, e.g. `0.1` varies the 
+    /// interval by up to 10% in either direction. This avoids many pollers retrying at the same 
+    /// moment. Default is `0.1`.
+    pub jitter: f64,
+}
+
+impl Default for PollBackoff {
+    fn default() -> Self {
+        Self {
+            multiplier: DEFAULT_MULTIPLIER,
+            // unwrap is safe because the value is in range
+            max_interval: Duration::try_seconds(DEFAULT_MAX_INTERVAL_SECONDS).unwrap(),
+            jitter: DEFAULT_JITTER,
+        }
+    }
+}
+
+impl PollBackoff {
+    /// Gets the interval to wait after `consecutive_errors` failed polls, where `interval` is the 
+    /// interval used when polls are successful.
+    pub fn interval(
+        &self,
+        interval: std::time::Duration,
+        consecutive_errors: u32,
+    ) -> std::time::Duration {
+        // A random factor in the range of -1.0 to 1.0.
+        let random = rand::random::<f64>() * 2.0 - 1.0;
+        
+        self.interval_with_random(interval, consecutive_errors, random)
+    }
+    
+    fn interval_with_random(
+        &self,
+        interval: std::time::Duration,
+        consecutive_errors: u32,
+        random: f64,
+    ) -> std::time::Duration {
+        let max_interval = self.max_interval.to_std()
+            .unwrap_or(interval)
+            .as_secs_f64();
+        // Limit the exponent so the value does not overflow.
+        let exponent = consecutive_errors.min(i32::MAX as u32) as i32;
+        let seconds = (interval.as_secs_f64() * self.multiplier.max(1.0).powi(exponent))
+            .min(max_interval);
+        let jitter = seconds * self.jitter.clamp(0.0, 1.0) * random;
+        
+        std::time::Duration::from_secs_f64((seconds + jitter).clamp(0.0, max_interval))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn backs_off_exponentially_up_to_max_interval() {
+        let backoff = PollBackoff {
+            jitter: 0.0,
+            ..PollBackoff::default()
+        };
+        let interval = std::time::Duration::from_secs(30);
+        
+        assert_eq!(backoff.interval_with_random(interval, 0, 0.0).as_secs(), 30);
+        assert_eq!(backoff.interval_with_random(interval, 1, 0.0).as_secs(), 60);
+        assert_eq!(backoff.interval_with_random(interval, 3, 0.0).as_secs(), 240);
+        assert_eq!(backoff.interval_with_random(interval, 100, 0.0).as_secs(), 600);
+    }
+    
+    #[test]
+    fn applies_jitter() {
+        let backoff = PollBackoff::default();
+        let interval = std::time::Duration::from_secs(30);
+        
+        assert_eq!(backoff.interval_with_random(interval, 1, 1.0).as_secs(), 66);
+        assert_eq!(backoff.interval_with_random(interval, 1, -1.0).as_secs(), 54);
+        // Jitter does not exceed the maximum interval.
+        assert_eq!(backoff.interval_with_random(interval, 100, 1.0).as_secs(), 600);
+    }
+}
\ No newline at end of file
@@ -1,13 +1,19 @@
 use crate::time::{date_difference_from_now, ServerTime};
-use crate::types::TradeOfferId;
+use crate::types::{TradeOfferId, AppId, ContextId, AssetId};
 use crate::enums::TradeOfferState;
+use crate::error::FileError;
 use std::collections::{HashMap, HashSet};
 use serde::{Serialize, Deserialize};
+use serde_json::Value;
 use chrono::Duration;
 
 /// Used for storing account poll data.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PollData {
+    /// The version of the format the poll data is stored in. Always 
+    /// [`PollData::CURRENT_VERSION`] once loaded.
+    #[serde(default = "unversioned")]
+    pub version: u32,
     #[serde(default)]
     /// Where to fetch offers since the last poll.
     pub offers_since: Option<ServerTime>,
@@ -20,12 +26,71 @@ pub struct PollData {
     #[serde(default)]
     /// The state map for trade offers.
     pub state_map: HashMap<TradeOfferId, TradeOfferState>,
+    #[serde(default)]
+    /// The escrow end dates for offers in escrow.
+    pub escrow_map: HashMap<TradeOfferId, ServerTime>,
+    #[serde(default)]
+    /// Custom data attached to offers.
+    pub offer_data: HashMap<TradeOfferId, Value>,
+    #[serde(default)]
+    /// The items already reported missing from each active offer.
+    pub missing_items: HashMap<TradeOfferId, HashSet<(AppId, ContextId, AssetId)>>,
     #[serde(default, skip_serializing)]
     /// Whether the data has changed. Used for reducing file writes.
     pub changed: bool,
 }
 
+impl Default for PollData {
+    fn default() -> Self {
+        Self {
+            version: Self::CURRENT_VERSION,
+            offers_since: None,
+            last_poll: None,
+            last_poll_full_update: None,
+            state_map: HashMap::new(),
+            escrow_map: HashMap::new(),
+            offer_data: HashMap::new(),
+            missing_items: HashMap::new(),
+            changed: false,
+        }
+    }
+}
+
 impl PollData {
+    /// The current version of the poll data format.
+    pub const CURRENT_VERSION: u32 = 2;
+    
+    /// Parses poll data from JSON, migrating data stored in older formats to the current format.
+    /// Data without a version is treated as version 1.
+    /// 
+    /// # Errors
+    /// - If the JSON could not be parsed.
+    /// - If the data is stored in a newer version than [`PollData::CURRENT_VERSION`].
+    pub fn from_json(data: &str) -> Result<Self, FileError> {
+        let mut value: Value = serde_json::from_str(data)?;
+        let mut version = value.get("version")
+            .and_then(Value::as_u64)
+            .map(|version| version as u32)
+            .unwrap_or(1);
+        
+        if version > Self::CURRENT_VERSION {
+            return Err(FileError::UnsupportedVersion(version));
+        }
+        
+        let is_migrated = version < Self::CURRENT_VERSION;
+        
+        while version < Self::CURRENT_VERSION {
+            value = migrate(value, version);
+            version += 1;
+        }
+        
+        let mut poll_data: Self = serde_json::from_value(value)?;
+        
+        // Migrated data should be saved in the current format.
+        poll_data.changed = is_migrated;
+        Ok(poll_data)
+    }
+    
     /// Checks if the last full poll is stale based on the `update_interval`.
     pub fn last_full_poll_is_stale(&self, update_interval: &Duration) -> bool {
         if let Some(last_poll_full_update) = self.last_poll_full_update {
@@ -45,10 +110,25 @@ impl PollData {
         let length = self.state_map.len();
         
         self.state_map.retain(|tradeofferid, _| tradeofferids_to_retain.contains(tradeofferid));
+        self.missing_items.retain(|tradeofferid, _| tradeofferids_to_retain.contains(tradeofferid));
         // If the length of the map has changed, then the state has changed.
         self.changed = self.changed || self.state_map.len() != length;
     }
     
+    /// Tracks the escrow end date for an offer in escrow.
+    pub fn set_escrow_end_date(&mut self, tradeofferid: TradeOfferId, date: ServerTime) {
+        if self.escrow_map.insert(tradeofferid, date) != Some(date) {
+            self.changed = true;
+        }
+    }
+    
+    /// Stops tracking the escrow end date for an offer.
+    pub fn remove_escrow(&mut self, tradeofferid: TradeOfferId) {
+        if self.escrow_map.remove(&tradeofferid).is_some() {
+            self.changed = true;
+        }
+    }
+    
     /// Updates the `offers_since` value.
     pub fn set_offers_since(&mut self, date: ServerTime) {
         if self.offers_since != Some(date) {
@@ -69,4 +149,49 @@ impl PollData {
             self.changed = true;
         }
     }
-}
\ No newline at end of file
+}
+
+/// Data stored before poll data was versioned is version 1.
+fn unversioned() -> u32 {
+    1
+}
+
+/// Migrates poll data in the layout of `version` to the layout of the following version.
+fn migrate(mut value: Value, version: u32) -> Value {
+    // Version 1 is the same layout without a version.
+    if version == 1 {
+        if let Some(object) = value.as_object_mut() {
+            object.insert("version".into(), Value::from(2));
+        }
+    }
+    
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn migrates_unversioned_poll_data() {
+        let poll_data = PollData::from_json(r#"{"offers_since":"2023-11-14T22:13:20Z","state_map":{"1":2}}"#).unwrap();
+        
+        assert_eq!(poll_data.version, PollData::CURRENT_VERSION);
+        assert_eq!(poll_data.state_map.get(&1), Some(&TradeOfferState::Active));
+        assert!(poll_data.offers_since.is_some());
+    }
+    
+    #[test]
+    fn rejects_newer_poll_data() {
+        let result = PollData::from_json(r#"{"version":9999,"state_map":{}}"#);
+        
+        assert!(matches!(result, Err(FileError::UnsupportedVersion(9999))));
+    }
+    
+    #[test]
+    fn deserializes_without_version() {
+        let poll_data: PollData = serde_json::from_str(r#"{"state_map":{}}"#).unwrap();
+        
+        assert_eq!(poll_data.version, 1);
+    }
+}
@@ -0,0 +1,217 @@
+use super::PollData;
+use crate::SteamID;
+use crate::error::{AnyhowError, FileError};
+use crate::helpers::write_file_atomic;
+use std::fmt;
+use std::path::PathBuf;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+
+/// Storage backend for [`PollData`]. Poll data is loaded when polling starts and saved whenever 
+/// it changes during a poll.
+/// 
+/// By default [`FilePollDataStore`] is used, which stores poll data as JSON files in the data 
+/// directory. Implement this trait to persist poll data elsewhere, e.g. in a database when the 
+/// local filesystem is not persistent.
+#[async_trait]
+pub trait PollDataStore: fmt::Debug + Send + Sync {
+    /// Loads the poll data for `steamid`. Returns `None` if no poll data is stored for the 
+    /// account.
+    async fn load(
+        &self,
+        steamid: SteamID,
+    ) -> Result<Option<PollData>, AnyhowError>;
+    
+    /// Saves the poll data for `steamid`.
+    async fn save(
+        &self,
+        steamid: SteamID,
+        poll_data: &PollData,
+    ) -> Result<(), AnyhowError>;
+}
+
+/// Stores poll data as `poll_data_<steamid>.json` files in a directory. Files in older formats 
+/// are migrated when loaded. Files which cannot be read are backed up as 
+/// `poll_data_<steamid>.<timestamp>.bak` rather than being overwritten.
+#[derive(Debug, Clone)]
+pub struct FilePollDataStore {
+    directory: PathBuf,
+}
+
+impl FilePollDataStore {
+    /// Creates a new [`FilePollDataStore`] storing files in `directory`.
+    pub fn new<T>(directory: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        Self {
+            directory: directory.into(),
+        }
+    }
+    
+    fn filepath(&self, steamid: SteamID) -> PathBuf {
+        self.directory.join(format!("poll_data_{}.json", u64::from(steamid)))
+    }
+}
+
+#[async_trait]
+impl PollDataStore for FilePollDataStore {
+    async fn load(
+        &self,
+        steamid: SteamID,
+    ) -> Result<Option<PollData>, AnyhowError> {
+        let filepath = self.filepath(steamid);
+        let result = match async_fs::read_to_string(&filepath).await {
+            Ok(data) => PollData::from_json(&data),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => Err(FileError::from(error)),
+        };
+        
+        match result {
+            Ok(poll_data) => Ok(Some(poll_data)),
+            Err(error) => {
+                // Keep the unreadable file so it is not lost when the poll data is next saved.
+                let backup_filepath = filepath.with_extension(format!(
+                    "{}.bak",
+                    chrono::Utc::now().timestamp(),
+                ));
+                
+                async_fs::rename(&filepath, &backup_filepath).await
+                    .map_err(FileError::from)?;
+                
+                Err(anyhow::Error::from(error)
+                    .context(format!("Poll data backed up to {}", backup_filepath.display())))
+            },
+        }
+    }
+    
+    async fn save(
+        &self,
+        steamid: SteamID,
+        poll_data: &PollData,
+    ) -> Result<(), AnyhowError> {
+        let data = serde_json::to_string(poll_data)
+            .map_err(FileError::from)?;
+        
+        write_file_atomic(self.filepath(steamid), data.as_bytes()).await
+            .map_err(FileError::from)?;
+        
+        Ok(())
+    }
+}
+
+/// Stores poll data in memory. Poll data is lost when the store is dropped, which makes this 
+/// useful for tests. Clones share the same underlying data.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPollDataStore {
+    inner: Arc<Mutex<HashMap<u64, PollData>>>,
+}
+
+impl MemoryPollDataStore {
+    /// Creates a new empty [`MemoryPollDataStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Gets a copy of the poll data stored for `steamid`.
+    pub fn get(&self, steamid: SteamID) -> Option<PollData> {
+        self.inner.lock().unwrap().get(&u64::from(steamid)).cloned()
+    }
+}
+
+#[async_trait]
+impl PollDataStore for MemoryPollDataStore {
+    async fn load(
+        &self,
+        steamid: SteamID,
+    ) -> Result<Option<PollData>, AnyhowError> {
+        Ok(self.get(steamid))
+    }
+    
+    async fn save(
+        &self,
+        steamid: SteamID,
+        poll_data: &PollData,
+    ) -> Result<(), AnyhowError> {
+        self.inner.lock().unwrap().insert(u64::from(steamid), poll_data.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::TradeOfferState;
+    
+    /// A directory unique to this test process.
+    fn temp_directory(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{name}_{}", std::process::id()))
+    }
+    
+    #[tokio::test]
+    async fn memory_store_saves_and_loads() {
+        let store = MemoryPollDataStore::new();
+        let steamid = SteamID::from(76561198000000000);
+        let mut poll_data = PollData::default();
+        
+        poll_data.state_map.insert(1, TradeOfferState::Active);
+        
+        assert!(store.load(steamid).await.unwrap().is_none());
+        
+        store.save(steamid, &poll_data).await.unwrap();
+        
+        let loaded = store.load(steamid).await.unwrap().unwrap();
+        
+        assert_eq!(loaded.state_map.get(&1), Some(&TradeOfferState::Active));
+    }
+    
+    #[tokio::test]
+    async fn file_store_returns_none_when_missing() {
+        let store = FilePollDataStore::new(temp_directory("poll_data_store_missing"));
+        let loaded = store.load(SteamID::from(76561198000000000)).await.unwrap();
+        
+        assert!(loaded.is_none());
+    }
+    
+    #[tokio::test]
+    async fn file_store_backs_up_unreadable_file() {
+        let directory = temp_directory("poll_data_store_unreadable");
+        let steamid = SteamID::from(76561198000000000);
+        let store = FilePollDataStore::new(&directory);
+        
+        let _ = async_fs::remove_dir_all(&directory).await;
+        async_fs::create_dir_all(&directory).await.unwrap();
+        async_fs::write(store.filepath(steamid), "not json").await.unwrap();
+        
+        assert!(store.load(steamid).await.is_err());
+        assert!(store.load(steamid).await.unwrap().is_none());
+        
+        let mut entries = async_fs::read_dir(&directory).await.unwrap();
+        let mut backups = 0;
+        
+        while let Some(entry) = futures::StreamExt::next(&mut entries).await {
+            if entry.unwrap().path().extension().is_some_and(|extension| extension == "bak") {
+                backups += 1;
+            }
+        }
+        
+        assert_eq!(backups, 1);
+        let _ = async_fs::remove_dir_all(&directory).await;
+    }
+    
+    #[tokio::test]
+    async fn file_store_backs_up_file_with_invalid_utf8() {
+        let directory = temp_directory("poll_data_store_invalid_utf8");
+        let steamid = SteamID::from(76561198000000000);
+        let store = FilePollDataStore::new(&directory);
+        
+        let _ = async_fs::remove_dir_all(&directory).await;
+        async_fs::create_dir_all(&directory).await.unwrap();
+        async_fs::write(store.filepath(steamid), [0xff, 0xfe]).await.unwrap();
+        
+        assert!(store.load(steamid).await.is_err());
+        assert!(store.load(steamid).await.unwrap().is_none());
+        let _ = async_fs::remove_dir_all(&directory).await;
+    }
+}
@@ -0,0 +1,138 @@
+use super::CancelReason;
+use crate::enums::TradeOfferState;
+use crate::response::TradeOffer;
+use crate::types::{TradeOfferId, ServerTime};
+use crate::api::response::{RawTradeOffer, RawAsset};
+use crate::error::{Error, MissingClassInfoError, AnyhowError};
+use std::sync::Arc;
+
+/// An event emitted by the poller. A single poll emits any number of offer events followed by
+/// either [`PollEvent::PollComplete`] or [`PollEvent::PollFailed`].
+#[derive(Debug, Clone)]
+pub enum PollEvent {
+    /// An offer sent to us that has not been encountered before. This is usually an offer in the
+    /// [`TradeOfferState::Active`] state.
+    NewReceivedOffer(TradeOffer),
+    /// An offer sent by us that has not been encountered before, e.g. an offer sent from another
+    /// client.
+    NewSentOffer(TradeOffer),
+    /// The state of an offer sent to us has changed.
+    ReceivedOfferChanged {
+        /// The offer.
+        offer: TradeOffer,
+        /// The previous state of the offer.
+        old_state: TradeOfferState,
+    },
+    /// The state of an offer sent by us has changed.
+    SentOfferChanged {
+        /// The offer.
+        offer: TradeOffer,
+        /// The previous state of the offer.
+        old_state: TradeOfferState,
+    },
+    /// An offer sent by us was cancelled by the poller because of the `cancel_policy`, 
+    /// `cancel_duration`, or `cancel_offer_count` in [`PollOptions`][super::PollOptions].
+    AutoCancelled {
+        /// The offer.
+        offer: TradeOffer,
+        /// The previous state of the offer. `None` if the offer was not encountered before.
+        old_state: Option<TradeOfferState>,
+        /// Why the offer was cancelled.
+        reason: CancelReason,
+    },
+    /// An offer which changed could not be combined with its descriptions because the classinfos 
+    /// for some of its items could not be obtained. The poller tries again on the next poll 
+    /// until the offer's descriptions are loaded. The offer's state is not updated in the poll 
+    /// data until then. After [`PollOptions::missing_classinfo_max_attempts`] attempts the 
+    /// poller gives up and the offer is not tried again unless its state changes.
+    /// 
+    /// [`PollOptions::missing_classinfo_max_attempts`]: super::PollOptions::missing_classinfo_max_attempts
+    OfferMissingClassInfo {
+        /// The offer.
+        offer: RawTradeOffer,
+        /// The missing classinfo.
+        error: Arc<MissingClassInfoError>,
+        /// The number of polls the descriptions for this offer have failed to load in a row, 
+        /// including this one.
+        attempts: u32,
+        /// Whether the poller gave up on loading the descriptions for this offer.
+        gave_up: bool,
+    },
+    /// Items in an active offer no longer exist in the inventory they belong to, e.g. an item left 
+    /// our partner's inventory. Steam usually invalidates the offer soon after. Each item is 
+    /// only reported once for an offer. Reported items are saved in the poll data so they are 
+    /// not reported again after a restart. Not emitted for offers cancelled during the poll.
+    OfferItemsMissing {
+        /// The offer.
+        offer: RawTradeOffer,
+        /// The items which disappeared since the offer was last polled.
+        items: Vec<RawAsset>,
+    },
+    /// An offer entered escrow. Its items are held by Steam until `escrow_end_date`, after which 
+    /// [`PollEvent::EscrowReleased`] is emitted. The change to the state of the offer is also 
+    /// emitted as a regular offer event.
+    OfferEnteredEscrow {
+        /// The ID of the offer.
+        tradeofferid: TradeOfferId,
+        /// When the items in the offer are released from escrow.
+        escrow_end_date: ServerTime,
+    },
+    /// An offer in escrow left escrow. Once the escrow end date for an offer has passed, the state 
+    /// of the offer is verified against the API before this is emitted. This is also emitted when 
+    /// a poll finds the offer is no longer in escrow. Changes to the state of the offer are also 
+    /// emitted as regular offer events.
+    EscrowReleased {
+        /// The ID of the offer.
+        tradeofferid: TradeOfferId,
+        /// When the items in the offer were released from escrow.
+        escrow_end_date: ServerTime,
+        /// The state of the offer after escrow ended. This is usually 
+        /// [`TradeOfferState::Accepted`].
+        trade_offer_state: TradeOfferState,
+    },
+    /// An offer sent by us was confirmed by the poller because `confirm_sent_offers` is set in 
+    /// [`PollOptions`][super::PollOptions].
+    SentOfferConfirmed(TradeOfferId),
+    /// The poller failed to accept the confirmation for an offer sent by us. The confirmation 
+    /// will be retried on the next poll.
+    SentOfferConfirmationFailed {
+        /// The ID of the offer.
+        tradeofferid: TradeOfferId,
+        /// The error encountered accepting the confirmation.
+        error: Arc<Error>,
+    },
+    /// The poll data could not be loaded when polling started. Polling continues using empty poll 
+    /// data, so offers may be reported again as new. This is emitted before any polls.
+    PollDataLoadFailed(Arc<AnyhowError>),
+    /// The poll failed. No further events are emitted for this poll.
+    PollFailed(Arc<Error>),
+    /// The poll completed successfully. All events for this poll have been emitted.
+    PollComplete,
+}
+
+impl PollEvent {
+    /// The offer this event relates to, if any.
+    pub fn offer(&self) -> Option<&TradeOffer> {
+        match self {
+            Self::NewReceivedOffer(offer) |
+            Self::NewSentOffer(offer) |
+            Self::ReceivedOfferChanged { offer, .. } |
+            Self::SentOfferChanged { offer, .. } |
+            Self::AutoCancelled { offer, .. } => Some(offer),
+            Self::OfferMissingClassInfo { .. } |
+            Self::OfferItemsMissing { .. } |
+            Self::OfferEnteredEscrow { .. } |
+            Self::EscrowReleased { .. } |
+            Self::SentOfferConfirmed(_) |
+            Self::SentOfferConfirmationFailed { .. } |
+            Self::PollDataLoadFailed(_) |
+            Self::PollFailed(_) |
+            Self::PollComplete => None,
+        }
+    }
+    
+    /// Whether this event marks the end of a poll.
+    pub fn is_end_of_poll(&self) -> bool {
+        matches!(self, Self::PollFailed(_) | Self::PollComplete)
+    }
+}
\ No newline at end of file
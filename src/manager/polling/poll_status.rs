@@ -0,0 +1,34 @@
+use super::PendingEscrow;
+use crate::time::ServerTime;
+
+/// A snapshot of what the polling task is doing. Useful for health checks, e.g. alerting when 
+/// `last_successful_poll` is too far in the past.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PollStatus {
+    /// When the poll currently being performed was started. `None` if no poll is in progress.
+    pub poll_started: Option<ServerTime>,
+    /// Whether polling at the interval is paused.
+    pub is_paused: bool,
+    /// When the last poll finished, whether it succeeded or not.
+    pub last_poll: Option<ServerTime>,
+    /// When the last successful poll finished.
+    pub last_successful_poll: Option<ServerTime>,
+    /// When the last successful full update finished.
+    pub last_full_update: Option<ServerTime>,
+    /// Whether the last attempted full update succeeded. `None` if no full update has been 
+    /// attempted since polling started.
+    pub last_full_update_succeeded: Option<bool>,
+    /// The number of polls that have failed in a row.
+    pub consecutive_errors: u32,
+    /// The number of polls performed since polling started.
+    pub polls: u64,
+    /// The number of polls that failed since polling started.
+    pub failed_polls: u64,
+    /// The number of offers tracked in the state map of the poll data.
+    pub tracked_offers: usize,
+    /// The number of offers whose descriptions could not be loaded and are waiting to be 
+    /// retried.
+    pub pending_offers: usize,
+    /// Offers in escrow, ordered by when they are released.
+    pub pending_escrow: Vec<PendingEscrow>,
+}
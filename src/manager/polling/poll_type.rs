@@ -19,11 +19,6 @@ pub enum PollType {
 }
 
 impl PollType {
-    /// The poll is a full update.
-    pub(crate) fn is_full_update(&self) -> bool {
-        matches!(self, Self::FullUpdate)
-    }
-    
     /// The poll is only active offers.
     pub(crate) fn is_active_only(&self) -> bool {
         matches!(self, Self::NewOffers)
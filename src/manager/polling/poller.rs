@@ -1,68 +1,290 @@
-use super::file;
-use super::{PollData, PollType};
+use super::{PollData, PollDataStore, PollType, PollEvent, PollOptions, PollStatus, PendingEscrow, CancelPolicy, CancelReason};
+use super::cancel_policy::oldest_offers_over_count;
+use crate::manager::offer_store::OfferStore;
+use crate::manager::offer_data::OfferData;
 use crate::api::request::GetTradeOffersOptions;
 use crate::time;
 use crate::enums::TradeOfferState;
 use crate::types::TradeOfferId;
-use crate::response::TradeOffer;
-use crate::api::SteamTradeOfferAPI;
+use crate::api::TradeOfferApi;
+use crate::api::response::RawTradeOffer;
+use crate::mobile_api::ConfirmationApi;
+use crate::time::ServerTime;
 use crate::error::Error;
 use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
 use chrono::Duration;
 use steamid_ng::SteamID;
 
-/// A poll containing new offers. For each item in the vector, the first element is the 
-/// [`TradeOffer`]. The second part is the previous [`TradeOfferState`] if this is not a newly
-/// encountered offer.
-pub type Poll = Vec<(TradeOffer, Option<TradeOfferState>)>;
-/// The result of a poll. Either containing a [`Poll`] or an [`Error`] if an error was encountered
-/// during a poll.
-pub type Result = std::result::Result<Poll, Error>;
-
 const OFFERS_SINCE_BUFFER_SECONDS: i64 = 60 * 30;
 const OFFERS_SINCE_ALL_TIMESTAMP: i64 = 1;
 
+/// An offer whose descriptions could not be loaded. These are retried on subsequent polls.
+pub struct PendingOffer {
+    pub offer: RawTradeOffer,
+    /// The number of polls the descriptions have failed to load in a row.
+    pub attempts: u32,
+}
+
 pub struct Poller {
     pub steamid: SteamID,
-    pub api: SteamTradeOfferAPI,
-    pub cancel_duration: Option<Duration>,
+    pub api: Arc<dyn TradeOfferApi>,
+    /// Used for confirming offers sent by the manager.
+    pub mobile_api: Arc<dyn ConfirmationApi>,
+    /// Whether to confirm offers sent by the manager.
+    pub confirm_sent_offers: bool,
+    /// Offers sent by the manager which are awaiting mobile confirmation.
+    pub awaiting_confirmation: Arc<Mutex<HashSet<TradeOfferId>>>,
+    pub cancel_policy: Option<Arc<dyn CancelPolicy>>,
+    pub cancel_offer_count: Option<usize>,
+    pub cancel_offer_count_min_age: Duration,
     pub poll_full_update_duration: Duration,
+    /// The regular interval between polls. Offers still in escrow after their escrow end date are 
+    /// checked again after this long.
+    pub poll_interval: Duration,
     pub poll_data: PollData,
+    pub store: Arc<dyn PollDataStore>,
+    /// Records offers from polls.
+    pub offer_store: Option<Arc<dyn OfferStore>>,
+    /// Custom data attached to offers, shared with the manager.
+    pub offer_data: Arc<Mutex<OfferData>>,
+    /// The number of polls that have failed in a row.
+    pub consecutive_errors: u32,
+    /// Offers whose descriptions could not be loaded.
+    pub pending_offers: HashMap<TradeOfferId, PendingOffer>,
+    /// The number of polls to try loading the descriptions for an offer before giving up on it.
+    pub missing_classinfo_max_attempts: u32,
+    /// When to check offers again whose escrow end date passed but were not released.
+    pub escrow_rechecks: HashMap<TradeOfferId, ServerTime>,
+    /// The status of the poller, shared with the manager.
+    pub status: Arc<RwLock<PollStatus>>,
 }
 
 impl Poller {
+    /// Applies the options relevant to performing polls.
+    pub fn set_options(
+        &mut self,
+        options: &PollOptions,
+    ) {
+        self.cancel_policy = options.get_cancel_policy();
+        self.cancel_offer_count = options.cancel_offer_count;
+        self.cancel_offer_count_min_age = options.cancel_offer_count_min_age;
+        self.poll_full_update_duration = options.poll_full_update_duration;
+        self.poll_interval = options.poll_interval;
+        self.confirm_sent_offers = options.confirm_sent_offers;
+        self.missing_classinfo_max_attempts = options.missing_classinfo_max_attempts;
+        
+        if self.confirm_sent_offers && !self.mobile_api.has_identity_secret() {
+            log::warn!("Cannot confirm sent offers without an identity secret");
+            self.confirm_sent_offers = false;
+        }
+    }
+    
     /// Performs a poll for changes to offers. `poll_type` determines the type of poll to perform.
+    /// 
+    /// The returned events always end with either [`PollEvent::PollComplete`] or 
+    /// [`PollEvent::PollFailed`].
     pub async fn do_poll(
         &mut self,
         poll_type: PollType,
-    ) -> Result {
+    ) -> Vec<PollEvent> {
+        let is_full_update = self.is_full_update(poll_type);
+        
+        self.update_status(|status| status.poll_started = Some(time::get_server_time_now()));
+        
+        let result = self.try_do_poll(poll_type, is_full_update).await;
+        let is_success = result.is_ok();
+        let events = match result {
+            Ok(mut events) => {
+                self.consecutive_errors = 0;
+                events.push(PollEvent::PollComplete);
+                events
+            },
+            Err(error) => {
+                self.consecutive_errors = self.consecutive_errors.saturating_add(1);
+                vec![PollEvent::PollFailed(Arc::new(error))]
+            },
+        };
+        let now = time::get_server_time_now();
+        let consecutive_errors = self.consecutive_errors;
+        let tracked_offers = self.poll_data.state_map.len();
+        let pending_offers = self.pending_offers.len();
+        let last_full_update = self.poll_data.last_poll_full_update;
+        let pending_escrow = self.pending_escrow();
+        
+        self.update_status(|status| {
+            status.poll_started = None;
+            status.last_poll = Some(now);
+            status.polls = status.polls.saturating_add(1);
+            status.consecutive_errors = consecutive_errors;
+            status.tracked_offers = tracked_offers;
+            status.pending_offers = pending_offers;
+            status.last_full_update = last_full_update;
+            status.pending_escrow = pending_escrow;
+            
+            if is_success {
+                status.last_successful_poll = Some(now);
+            } else {
+                status.failed_polls = status.failed_polls.saturating_add(1);
+            }
+            
+            if is_full_update {
+                status.last_full_update_succeeded = Some(is_success);
+            }
+        });
+        
+        events
+    }
+    
+    /// Merges the custom offer data from the poll data into the data shared with the manager. 
+    /// Data set on the manager takes precedence.
+    pub fn load_offer_data(&mut self) {
+        if let Ok(mut offer_data) = self.offer_data.lock() {
+            let map = std::mem::take(&mut self.poll_data.offer_data);
+            
+            if offer_data.is_loaded {
+                // The manager already loaded the data from the store.
+                offer_data.changed = true;
+            } else {
+                offer_data.load(map);
+            }
+        }
+    }
+    
+    /// Copies the custom offer data shared with the manager into the poll data if it changed.
+    fn sync_offer_data(&mut self) {
+        if let Ok(mut offer_data) = self.offer_data.lock() {
+            if offer_data.changed {
+                offer_data.changed = false;
+                self.poll_data.offer_data = offer_data.map.clone();
+                self.poll_data.changed = true;
+            }
+        }
+    }
+    
+    /// Offers in escrow, ordered by when they are released.
+    pub fn pending_escrow(&self) -> Vec<PendingEscrow> {
+        let mut pending_escrow = self.poll_data.escrow_map
+            .iter()
+            .map(|(tradeofferid, escrow_end_date)| PendingEscrow {
+                tradeofferid: *tradeofferid,
+                escrow_end_date: *escrow_end_date,
+            })
+            .collect::<Vec<_>>();
+        
+        pending_escrow.sort_by_key(|pending_escrow| pending_escrow.escrow_end_date);
+        pending_escrow
+    }
+    
+    /// When the next offer in escrow is released, or when an offer which was not released at its 
+    /// escrow end date should be checked again.
+    pub fn next_escrow_end(&self) -> Option<tokio::time::Instant> {
+        let escrow_end_date = self.poll_data.escrow_map
+            .iter()
+            .map(|(tradeofferid, escrow_end_date)| {
+                self.escrow_check_date(*tradeofferid, *escrow_end_date)
+            })
+            .min()?;
+        // Already passed dates are polled for right away.
+        let duration = (escrow_end_date - time::get_server_time_now())
+            .to_std()
+            .unwrap_or_default();
+        
+        Some(tokio::time::Instant::now() + duration)
+    }
+    
+    /// When to check whether an offer in escrow was released.
+    fn escrow_check_date(
+        &self,
+        tradeofferid: TradeOfferId,
+        escrow_end_date: ServerTime,
+    ) -> ServerTime {
+        match self.escrow_rechecks.get(&tradeofferid) {
+            Some(recheck_date) => escrow_end_date.max(*recheck_date),
+            None => escrow_end_date,
+        }
+    }
+    
+    /// Updates the shared status.
+    pub fn update_status<F>(&self, f: F)
+    where
+        F: FnOnce(&mut PollStatus),
+    {
+        if let Ok(mut status) = self.status.write() {
+            f(&mut status);
+        }
+    }
+    
+    /// Whether the poll should fetch all offers.
+    fn is_full_update(&self, poll_type: PollType) -> bool {
+        match poll_type {
+            PollType::NewOffers |
+            PollType::OffersSince(_) => false,
+            PollType::FullUpdate => true,
+            // The date of the last full poll is outdated.
+            PollType::Auto => self.poll_data.last_full_poll_is_stale(&self.poll_full_update_duration),
+        }
+    }
+    
+    /// Gets the pending offers to try again which are not already in `poll`.
+    fn pending_offers_to_retry(
+        &self,
+        poll: &[RawTradeOffer],
+        prev_states_map: &mut HashMap<TradeOfferId, TradeOfferState>,
+    ) -> Vec<RawTradeOffer> {
+        let polled_tradeofferids = poll
+            .iter()
+            .map(|offer| offer.tradeofferid)
+            .collect::<HashSet<_>>();
+        let mut retried_offers = Vec::new();
+        
+        for (tradeofferid, pending_offer) in &self.pending_offers {
+            if polled_tradeofferids.contains(tradeofferid) {
+                continue;
+            }
+            
+            if let Some(state) = self.poll_data.state_map.get(tradeofferid) {
+                prev_states_map.insert(*tradeofferid, *state);
+            }
+            
+            retried_offers.push(pending_offer.offer.clone());
+        }
+        
+        retried_offers
+    }
+    
+    /// Whether any offers are active and awaiting a response.
+    pub fn has_active_offers(&self) -> bool {
+        self.poll_data.state_map
+            .values()
+            .any(|state| *state == TradeOfferState::Active)
+    }
+    
+    async fn try_do_poll(
+        &mut self,
+        poll_type: PollType,
+        is_full_update: bool,
+    ) -> Result<Vec<PollEvent>, Error> {
         let now = time::get_server_time_now();
         let mut offers_since = self.poll_data.offers_since
             // Steam can be dumb and backdate a modified offer. We need to handle this by adding a buffer.
             .map(|date| date.timestamp() - OFFERS_SINCE_BUFFER_SECONDS)
             .unwrap_or(OFFERS_SINCE_ALL_TIMESTAMP);
         let mut active_only = true;
-        let mut is_full_update = {
-            poll_type.is_full_update() || 
-            // The date of the last full poll is outdated.
-            self.poll_data.last_full_poll_is_stale(&self.poll_full_update_duration)
-        };
         
         if poll_type == PollType::NewOffers {
             // a very high date
             offers_since = u32::MAX as i64;
-            is_full_update = false;
         } else if let PollType::OffersSince(date) = poll_type {
             offers_since = date.timestamp();
             active_only = false;
-            is_full_update = false;
         } else if is_full_update {
             offers_since = OFFERS_SINCE_ALL_TIMESTAMP;
             active_only = false;
         }
         
         let (
-            mut offers,
+            offers,
             descriptions,
         ) = self.api.get_raw_trade_offers(&GetTradeOffersOptions {
             active_only,
@@ -81,34 +303,15 @@ impl Poller {
             self.poll_data.set_last_poll_full_update(now);
         }
         
-        // Vec of offers that were cancelled.
-        let cancelled_offers = if let Some(cancel_duration) = self.cancel_duration {
-            let cancel_time = chrono::Utc::now() - cancel_duration;
-            // Cancels all offers older than cancel_time.
-            let cancel_futures = offers
-                .iter_mut()
-                .filter(|offer| {
-                    let is_active_state = {
-                        offer.trade_offer_state == TradeOfferState::Active ||
-                        offer.trade_offer_state == TradeOfferState::CreatedNeedsConfirmation
-                    };
-                    
-                    is_active_state &&
-                    offer.is_our_offer &&
-                    offer.time_updated < cancel_time &&
-                    // offers with a tradeid are in progress and cannot be cancelled
-                    offer.tradeid.is_none()
-                })
-                .map(|offer| self.api.cancel_offer(offer.tradeofferid))
-                .collect::<Vec<_>>();
-            
-            futures::future::join_all(cancel_futures).await
-                .into_iter()
-                .filter_map(|offer| offer.ok())
-                .collect::<Vec<_>>()
-        } else {
-            Vec::new()
-        };
+        // Map of offers that were cancelled along with the reason they were cancelled.
+        let cancelled_offers = self.cancel_offers(&offers, now).await;
+        let confirmation_events = self.confirm_sent_offers(&offers, &cancelled_offers).await;
+        
+        let mut escrow_events = self.track_escrow(&offers);
+        let missing_items_events = self.detect_missing_items(&offers, &cancelled_offers);
+        
+        escrow_events.extend(self.check_escrow(now).await);
+        
         // For reducing file writes, keep track of whether the state of poll data has changed.
         let mut prev_states_map: HashMap<TradeOfferId, TradeOfferState> = HashMap::new();
         let mut poll: Vec<_> = Vec::new();
@@ -120,7 +323,7 @@ impl Poller {
         for mut offer in offers {
             // This offer was successfully cancelled above...
             // We need to update its state here.
-            if cancelled_offers.contains(&offer.tradeofferid) {
+            if cancelled_offers.contains_key(&offer.tradeofferid) {
                 offer.trade_offer_state = TradeOfferState::Canceled;
             }
             
@@ -148,7 +351,10 @@ impl Poller {
                     poll.push(offer);
                 },
                 // Nothing has changed...
-                Some(_) => {},
+                Some(_) => {
+                    // Any pending version of this offer is outdated.
+                    self.pending_offers.remove(&offer.tradeofferid);
+                },
                 // This is a new offer
                 None => poll.push(offer),
             }
@@ -163,44 +369,446 @@ impl Poller {
             self.poll_data.retain_offers(&retained_tradeofferids);
         }
         
+        // Try again for offers whose descriptions could not be loaded in previous polls.
+        let retried_offers = self.pending_offers_to_retry(&poll, &mut prev_states_map);
         // Maps raw offers to offers with classinfo descriptions.
-        let offers = if let Some(descriptions) = descriptions {
-            self.api.map_raw_trade_offers_with_descriptions(poll, descriptions)
+        let (offers, unmapped) = if let Some(descriptions) = descriptions {
+            let (
+                mut offers,
+                mut unmapped,
+            ) = self.api.map_raw_trade_offers_with_descriptions(poll, descriptions);
+            
+            // The descriptions in the response only cover the offers in the response.
+            if !retried_offers.is_empty() {
+                let (
+                    retried_offers,
+                    retried_unmapped,
+                ) = self.api.map_raw_trade_offers(retried_offers).await?;
+                
+                offers.extend(retried_offers);
+                unmapped.extend(retried_unmapped);
+            }
+            
+            (offers, unmapped)
         } else {
+            poll.extend(retried_offers);
             self.api.map_raw_trade_offers(poll).await?
         };
-        let poll = if offers.is_empty() {
-            // map_raw_trade_offers may have excluded some offers - the state of the poll data
-            // is not updated until all descriptions are loaded for the offer
-            Vec::new()
-        } else {
+        let mut events = unmapped
+            .into_iter()
+            .map(|unmapped| {
+                let tradeofferid = unmapped.offer.tradeofferid;
+                let attempts = self.pending_offers.get(&tradeofferid)
+                    .map(|pending_offer| pending_offer.attempts.saturating_add(1))
+                    .unwrap_or(1);
+                let gave_up = attempts >= self.missing_classinfo_max_attempts;
+                
+                if gave_up {
+                    self.pending_offers.remove(&tradeofferid);
+                    // The state is recorded so that the offer is not tried again until it 
+                    // changes.
+                    self.poll_data.state_map.insert(tradeofferid, unmapped.offer.trade_offer_state);
+                    self.poll_data.changed = true;
+                } else {
+                    self.pending_offers.insert(tradeofferid, PendingOffer {
+                        offer: unmapped.offer.clone(),
+                        attempts,
+                    });
+                }
+                
+                PollEvent::OfferMissingClassInfo {
+                    offer: unmapped.offer,
+                    error: Arc::new(unmapped.error),
+                    attempts,
+                    gave_up,
+                }
+            })
+            .collect::<Vec<_>>();
+        
+        // The state of the poll data is not updated until all descriptions are loaded for the 
+        // offer.
+        if !offers.is_empty() {
             self.poll_data.changed = true;
-            offers
+        }
+        
+        {
+            // The lock is released before awaiting.
+            let mut offer_data = self.offer_data.lock().ok();
+            
+            events.extend(offers
                 .into_iter()
-                // Combines changed state maps.
-                .map(|offer| {
-                    let prev_state = prev_states_map.remove(&offer.tradeofferid);
+                .map(|mut offer| {
+                    offer.data = offer_data
+                        .as_mut()
+                        .and_then(|offer_data| if is_final_state(offer.trade_offer_state) {
+                            // The offer will not change again.
+                            offer_data.remove(offer.tradeofferid)
+                        } else {
+                            offer_data.map.get(&offer.tradeofferid).cloned()
+                        });
                     
+                    let old_state = prev_states_map.remove(&offer.tradeofferid);
+                    
+                    self.pending_offers.remove(&offer.tradeofferid);
                     // insert new state into map
                     self.poll_data.state_map.insert(offer.tradeofferid, offer.trade_offer_state);
                     
-                    (offer, prev_state)
-                })
-                .collect::<Vec<_>>()
-        };
+                    if let Some(reason) = cancelled_offers.get(&offer.tradeofferid) {
+                        return PollEvent::AutoCancelled {
+                            offer,
+                            old_state,
+                            reason: *reason,
+                        };
+                    }
+                    
+                    match (offer.is_our_offer, old_state) {
+                        (true, Some(old_state)) => PollEvent::SentOfferChanged {
+                            offer,
+                            old_state,
+                        },
+                        (true, None) => PollEvent::NewSentOffer(offer),
+                        (false, Some(old_state)) => PollEvent::ReceivedOfferChanged {
+                            offer,
+                            old_state,
+                        },
+                        (false, None) => PollEvent::NewReceivedOffer(offer),
+                    }
+                }));
+        }
+        
+        if let Some(offer_store) = &self.offer_store {
+            let offers = events
+                .iter()
+                .filter_map(PollEvent::offer)
+                .collect::<Vec<_>>();
+            
+            if let Err(error) = offer_store.record_many(self.steamid, &offers, now).await {
+                log::warn!("Error recording offers: {error}");
+            }
+        }
+        
+        events.extend(missing_items_events);
+        events.extend(escrow_events);
+        events.extend(confirmation_events);
+        
+        self.sync_offer_data();
         
         // Only save if changes were detected.
         if self.poll_data.changed {
-            self.poll_data.changed = false;
-            // It's really not a problem to await on this.
-            // Saving the file takes a negligible amount of time (usually under a ms on an SSD).
-            let _ = file::save_poll_data(
-                self.steamid,
-                &serde_json::to_string(&self.poll_data)?,
-                &self.api.data_directory,
-            ).await;
+            self.save_poll_data().await;
+        }
+        
+        Ok(events)
+    }
+    
+    /// Saves the poll data to the store.
+    pub async fn save_poll_data(&mut self) {
+        self.sync_offer_data();
+        self.poll_data.changed = false;
+        // It's really not a problem to await on this.
+        // Saving the file takes a negligible amount of time (usually under a ms on an SSD).
+        if let Err(error) = self.store.save(self.steamid, &self.poll_data).await {
+            log::warn!("Error saving poll data: {error}");
+        }
+    }
+    
+    /// Cancels offers sent by us according to the cancel policy and cancel offer count. Returns 
+    /// the offers that were successfully cancelled along with the reason they were cancelled.
+    async fn cancel_offers(
+        &self,
+        offers: &[RawTradeOffer],
+        now: ServerTime,
+    ) -> HashMap<TradeOfferId, CancelReason> {
+        if self.cancel_policy.is_none() && self.cancel_offer_count.is_none() {
+            return HashMap::new();
+        }
+        
+        let cancellable_offers = offers
+            .iter()
+            .filter(|offer| {
+                let is_active_state = {
+                    offer.trade_offer_state == TradeOfferState::Active ||
+                    offer.trade_offer_state == TradeOfferState::CreatedNeedsConfirmation
+                };
+                
+                is_active_state &&
+                offer.is_our_offer &&
+                // offers with a tradeid are in progress and cannot be cancelled
+                offer.tradeid.is_none()
+            })
+            .collect::<Vec<_>>();
+        let mut offers_to_cancel = HashMap::new();
+        
+        if let Some(cancel_policy) = &self.cancel_policy {
+            for offer in &cancellable_offers {
+                if cancel_policy.should_cancel(offer, now) {
+                    offers_to_cancel.insert(offer.tradeofferid, CancelReason::Policy);
+                }
+            }
+        }
+        
+        if let Some(cancel_offer_count) = self.cancel_offer_count {
+            // Only active offers count towards the limit.
+            let active_offers = cancellable_offers
+                .iter()
+                .copied()
+                .filter(|offer| {
+                    offer.trade_offer_state == TradeOfferState::Active &&
+                    !offers_to_cancel.contains_key(&offer.tradeofferid)
+                });
+            
+            for tradeofferid in oldest_offers_over_count(
+                active_offers,
+                cancel_offer_count,
+                self.cancel_offer_count_min_age,
+                now,
+            ) {
+                offers_to_cancel.insert(tradeofferid, CancelReason::OfferCount);
+            }
+        }
+        
+        let cancel_futures = offers_to_cancel
+            .iter()
+            .map(|(tradeofferid, reason)| async move {
+                self.api.cancel_offer(*tradeofferid).await
+                    .map(|tradeofferid| (tradeofferid, *reason))
+            })
+            .collect::<Vec<_>>();
+        
+        futures::future::join_all(cancel_futures).await
+            .into_iter()
+            .filter_map(|result| match result {
+                Ok(cancelled) => Some(cancelled),
+                Err(error) => {
+                    log::warn!("Error cancelling offer: {error}");
+                    None
+                },
+            })
+            .collect()
+    }
+    
+    /// Accepts confirmations for offers sent by the manager which are awaiting confirmation. 
+    /// Offers which are no longer awaiting confirmation are no longer tracked. Returns an event 
+    /// for each confirmation accepted or failed.
+    async fn confirm_sent_offers(
+        &self,
+        offers: &[RawTradeOffer],
+        cancelled_offers: &HashMap<TradeOfferId, CancelReason>,
+    ) -> Vec<PollEvent> {
+        if !self.confirm_sent_offers {
+            return Vec::new();
+        }
+        
+        let mobile_api = &self.mobile_api;
+        let awaiting_confirmation = {
+            let Ok(mut awaiting_confirmation) = self.awaiting_confirmation.lock() else {
+                return Vec::new();
+            };
+            
+            for offer in offers {
+                let is_awaiting_confirmation = {
+                    offer.trade_offer_state == TradeOfferState::CreatedNeedsConfirmation &&
+                    !cancelled_offers.contains_key(&offer.tradeofferid)
+                };
+                
+                if !is_awaiting_confirmation {
+                    awaiting_confirmation.remove(&offer.tradeofferid);
+                }
+            }
+            
+            awaiting_confirmation.clone()
+        };
+        
+        if awaiting_confirmation.is_empty() {
+            return Vec::new();
+        }
+        
+        let confirmations = match mobile_api.get_trade_confirmations().await {
+            Ok(confirmations) => confirmations,
+            Err(error) => {
+                log::warn!("Error getting trade confirmations: {error}");
+                return Vec::new();
+            },
+        };
+        let mut events = Vec::new();
+        
+        // Confirmations which are not yet listed are tried again on the next poll.
+        for confirmation in confirmations {
+            let tradeofferid = confirmation.creator_id;
+            
+            if !awaiting_confirmation.contains(&tradeofferid) {
+                continue;
+            }
+            
+            match mobile_api.accept_confirmation(&confirmation).await {
+                Ok(()) => {
+                    if let Ok(mut awaiting_confirmation) = self.awaiting_confirmation.lock() {
+                        awaiting_confirmation.remove(&tradeofferid);
+                    }
+                    
+                    events.push(PollEvent::SentOfferConfirmed(tradeofferid));
+                },
+                Err(error) => events.push(PollEvent::SentOfferConfirmationFailed {
+                    tradeofferid,
+                    error: Arc::new(error),
+                }),
+            }
         }
         
-        Ok(poll)
+        events
     }
-}
\ No newline at end of file
+    
+    /// Tracks the escrow end dates for offers in escrow. Offers which are no longer in escrow are 
+    /// no longer tracked. Returns an event for each offer which entered escrow and for each 
+    /// tracked offer which left escrow.
+    fn track_escrow(
+        &mut self,
+        offers: &[RawTradeOffer],
+    ) -> Vec<PollEvent> {
+        let mut events = Vec::new();
+        
+        for offer in offers {
+            match (offer.trade_offer_state, offer.escrow_end_date) {
+                (TradeOfferState::InEscrow, Some(escrow_end_date)) => {
+                    if !self.poll_data.escrow_map.contains_key(&offer.tradeofferid) {
+                        events.push(PollEvent::OfferEnteredEscrow {
+                            tradeofferid: offer.tradeofferid,
+                            escrow_end_date,
+                        });
+                    }
+                    
+                    self.poll_data.set_escrow_end_date(offer.tradeofferid, escrow_end_date);
+                },
+                // Escrowed offers are checked against the API once their escrow end date passes.
+                (TradeOfferState::InEscrow, None) => {},
+                (trade_offer_state, _) => {
+                    let Some(
+                        escrow_end_date,
+                    ) = self.poll_data.escrow_map.get(&offer.tradeofferid).copied() else {
+                        continue;
+                    };
+                    
+                    self.poll_data.remove_escrow(offer.tradeofferid);
+                    self.escrow_rechecks.remove(&offer.tradeofferid);
+                    events.push(PollEvent::EscrowReleased {
+                        tradeofferid: offer.tradeofferid,
+                        escrow_end_date,
+                        trade_offer_state,
+                    });
+                },
+            }
+        }
+        
+        events
+    }
+    
+    /// Checks the state of offers whose escrow end date has passed. Returns an event for each 
+    /// offer released from escrow. Offers still in escrow, or which could not be checked, are 
+    /// checked again after the poll interval.
+    async fn check_escrow(
+        &mut self,
+        now: ServerTime,
+    ) -> Vec<PollEvent> {
+        // Offers which are no longer tracked do not need to be checked again.
+        self.escrow_rechecks.retain(|tradeofferid, _| {
+            self.poll_data.escrow_map.contains_key(tradeofferid)
+        });
+        
+        let released = self.poll_data.escrow_map
+            .iter()
+            .filter(|(tradeofferid, escrow_end_date)| {
+                self.escrow_check_date(**tradeofferid, **escrow_end_date) <= now
+            })
+            .map(|(tradeofferid, escrow_end_date)| (*tradeofferid, *escrow_end_date))
+            .collect::<Vec<_>>();
+        let recheck_date = now + self.poll_interval;
+        let mut events = Vec::new();
+        
+        for (tradeofferid, escrow_end_date) in released {
+            let offer = match self.api.get_trade_offer(tradeofferid).await {
+                Ok(offer) => offer,
+                Err(error) => {
+                    log::warn!("Error getting offer {tradeofferid} released from escrow: {error}");
+                    self.escrow_rechecks.insert(tradeofferid, recheck_date);
+                    continue;
+                },
+            };
+            
+            // Steam has not updated the offer yet.
+            if offer.trade_offer_state == TradeOfferState::InEscrow {
+                self.escrow_rechecks.insert(tradeofferid, recheck_date);
+                continue;
+            }
+            
+            self.poll_data.remove_escrow(tradeofferid);
+            self.escrow_rechecks.remove(&tradeofferid);
+            events.push(PollEvent::EscrowReleased {
+                tradeofferid,
+                escrow_end_date,
+                trade_offer_state: offer.trade_offer_state,
+            });
+        }
+        
+        events
+    }
+    
+    /// Detects items which disappeared from active offers since they were last polled. Returns an 
+    /// event for each offer with newly missing items. Offers which are no longer active, 
+    /// including offers cancelled during this poll, are no longer tracked.
+    fn detect_missing_items(
+        &mut self,
+        offers: &[RawTradeOffer],
+        cancelled_offers: &HashMap<TradeOfferId, CancelReason>,
+    ) -> Vec<PollEvent> {
+        let mut events = Vec::new();
+        
+        for offer in offers {
+            let is_active = offer.trade_offer_state == TradeOfferState::Active
+                && !cancelled_offers.contains_key(&offer.tradeofferid);
+            
+            if !is_active {
+                if self.poll_data.missing_items.remove(&offer.tradeofferid).is_some() {
+                    self.poll_data.changed = true;
+                }
+                
+                continue;
+            }
+            
+            let known_missing_items = self.poll_data.missing_items
+                .entry(offer.tradeofferid)
+                .or_default();
+            let items = offer.missing_items()
+                .filter(|asset| known_missing_items.insert((asset.appid, asset.contextid, asset.assetid)))
+                .copied()
+                .collect::<Vec<_>>();
+            
+            if known_missing_items.is_empty() {
+                self.poll_data.missing_items.remove(&offer.tradeofferid);
+            }
+            
+            if !items.is_empty() {
+                self.poll_data.changed = true;
+                events.push(PollEvent::OfferItemsMissing {
+                    offer: offer.clone(),
+                    items,
+                });
+            }
+        }
+        
+        events
+    }
+}
+
+/// Whether an offer in this state will not change state again.
+fn is_final_state(trade_offer_state: TradeOfferState) -> bool {
+    matches!(
+        trade_offer_state,
+        TradeOfferState::Accepted |
+        TradeOfferState::Countered |
+        TradeOfferState::Expired |
+        TradeOfferState::Canceled |
+        TradeOfferState::Declined |
+        TradeOfferState::InvalidItems |
+        TradeOfferState::CanceledBySecondFactor
+    )
+}
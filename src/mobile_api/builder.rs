@@ -1,8 +1,11 @@
 use super::MobileAPI;
 use crate::helpers::USER_AGENT_STRING;
+use crate::helpers::DEFAULT_COMMUNITY_URL;
+use crate::{RateLimiter, RetryPolicy};
 use std::sync::Arc;
 use reqwest::cookie::Jar;
 use reqwest_middleware::ClientWithMiddleware;
+use url::Url;
 
 /// Builder for constructing a [`MobileAPI`].
 #[derive(Debug, Clone)]
@@ -15,6 +18,12 @@ pub struct MobileAPIBuilder {
     pub(crate) client: Option<ClientWithMiddleware>,
     /// User agent for requests.
     pub(crate) user_agent: &'static str,
+    /// The base URL for Steam Community requests.
+    pub(crate) community_url: Url,
+    /// Limits the rate of requests sent by the default client.
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    /// Retries requests which fail due to transient errors.
+    pub(crate) retry_policy: Option<RetryPolicy>,
     /// How many seconds your computer is behind Steam's servers. Used in mobile confirmations.
     pub(crate) time_offset: i64,
 }
@@ -33,6 +42,9 @@ impl MobileAPIBuilder {
             cookies: None,
             client: None,
             user_agent: USER_AGENT_STRING,
+            community_url: DEFAULT_COMMUNITY_URL.clone(),
+            rate_limiter: None,
+            retry_policy: None,
             time_offset: 0,
         }
     }
@@ -51,6 +63,32 @@ impl MobileAPIBuilder {
         self
     }
     
+    /// Limits the rate of confirmation requests, which use the Steam Community budget. Requests 
+    /// are not limited if this is not set. See [`RateLimiter`] for sharing the budget with a 
+    /// [`SteamTradeOfferAPI`][crate::api::SteamTradeOfferAPI] for the same account and for using 
+    /// it with a client provided using `client`.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+    
+    /// Retries loading confirmations when the request fails due to a transient error. Accepting 
+    /// and cancelling confirmations are never retried, since Steam may have applied them. 
+    /// Requests are not retried if this is not set. This has no effect when a client is provided 
+    /// using `client`; add the [`RetryPolicy`] to the client's middleware instead.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+    
+    /// The base URL for loading, accepting, and cancelling mobile confirmations. Default is 
+    /// `https://steamcommunity.com`. When trade offer requests are sent to a proxy or a local 
+    /// server, set this to the same URL so that confirmations go there as well.
+    pub fn community_url(mut self, community_url: Url) -> Self {
+        self.community_url = community_url;
+        self
+    }
+    
     /// How many seconds your computer is behind Steam's servers. Used in mobile confirmations.
     pub fn time_offset(mut self, time_offset: i64) -> Self {
         self.time_offset = time_offset;
@@ -0,0 +1,78 @@
+use super::MobileAPI;
+use crate::response::Confirmation;
+use crate::error::Error;
+use std::fmt;
+use async_trait::async_trait;
+
+/// The mobile confirmation requests used by [`TradeOfferManager`][crate::TradeOfferManager] and 
+/// its poller.
+/// 
+/// [`MobileAPI`] is used by default. Implement this trait to substitute the requests, e.g. with a 
+/// stub returning fixed confirmations or failures when testing code built on the manager. Set 
+/// using 
+/// [`TradeOfferManagerBuilder::confirmation_api`][crate::TradeOfferManagerBuilder::confirmation_api].
+#[async_trait]
+pub trait ConfirmationApi: fmt::Debug + Send + Sync {
+    /// Sets cookies. Called with the cookies passed to the manager.
+    fn set_cookies(
+        &self,
+        cookies: &[String],
+    );
+    
+    /// Whether an identity secret is set. Polling cannot confirm sent offers without an identity 
+    /// secret. Default is `true`.
+    fn has_identity_secret(&self) -> bool {
+        true
+    }
+    
+    /// Gets the trade confirmations.
+    async fn get_trade_confirmations(
+        &self,
+    ) -> Result<Vec<Confirmation>, Error>;
+    
+    /// Accepts a confirmation.
+    async fn accept_confirmation(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<(), Error>;
+    
+    /// Cancels a confirmation.
+    async fn cancel_confirmation(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<(), Error>;
+}
+
+#[async_trait]
+impl ConfirmationApi for MobileAPI {
+    fn set_cookies(
+        &self,
+        cookies: &[String],
+    ) {
+        MobileAPI::set_cookies(self, cookies)
+    }
+    
+    fn has_identity_secret(&self) -> bool {
+        self.identity_secret.is_some()
+    }
+    
+    async fn get_trade_confirmations(
+        &self,
+    ) -> Result<Vec<Confirmation>, Error> {
+        MobileAPI::get_trade_confirmations(self).await
+    }
+    
+    async fn accept_confirmation(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<(), Error> {
+        MobileAPI::accept_confirmation(self, confirmation).await
+    }
+    
+    async fn cancel_confirmation(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<(), Error> {
+        MobileAPI::cancel_confirmation(self, confirmation).await
+    }
+}
@@ -6,10 +6,12 @@
 
 mod builder;
 mod operation;
+mod confirmation_api;
 
 use operation::Operation;
 
 pub use builder::MobileAPIBuilder;
+pub use confirmation_api::ConfirmationApi;
 
 use crate::SteamID;
 use crate::response::Confirmation;
@@ -20,7 +22,7 @@ use crate::helpers::{
     get_sessionid_and_steamid_from_cookies,
     get_default_middleware,
 };
-use crate::helpers::COMMUNITY_HOSTNAME;
+use crate::helpers::join_url;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::sync::atomic::{Ordering, AtomicU64};
@@ -46,12 +48,11 @@ pub struct MobileAPI {
     sessionid: Arc<RwLock<Option<String>>>,
     /// The SteamID  of the logged in user. `0` if no login cookies were passed.
     steamid: Arc<AtomicU64>,
+    /// The base URL for Steam Community requests.
+    community_url: Url,
 }
 
 impl MobileAPI {
-    /// Hostname for requests.
-    const HOSTNAME: &'static str = COMMUNITY_HOSTNAME;
-    
     /// Builder for constructing a [`MobileAPI`].
     pub fn builder() -> MobileAPIBuilder {
         MobileAPIBuilder::new()
@@ -76,10 +77,6 @@ impl MobileAPI {
             cookies.push(format!("sessionid={sessionid}"));
             sessionid
         };
-        // Should not panic since the URL is hardcoded.
-        let url = format!("https://{}", Self::HOSTNAME).parse::<Url>()
-            .unwrap_or_else(|error| panic!("URL could not be parsed from {}: {}", Self::HOSTNAME, error));
-        
         *self.sessionid.write().unwrap() = Some(sessionid);
         
         if let Some(steamid) = steamid {
@@ -87,7 +84,7 @@ impl MobileAPI {
         }
         
         for cookie_str in &cookies {
-            self.cookies.add_cookie_str(cookie_str, &url);
+            self.cookies.add_cookie_str(cookie_str, &self.community_url);
         }
     }
     
@@ -137,7 +134,7 @@ impl MobileAPI {
             pub conf: Vec<Confirmation>,
         }
         
-        let uri = Self::get_url("/mobileconf/getlist");
+        let uri = self.get_url("/mobileconf/getlist");
         let query = self.get_confirmation_query_params(Tag::Conf)?;
         let response = self.client.get(&uri)
             .header("X-Requested-With", "com.valvesoftware.android.steam.community")
@@ -193,7 +190,7 @@ impl MobileAPI {
         query.insert("cid", id.to_string());
         query.insert("ck", nonce.to_string());
         
-        let uri = Self::get_url("/mobileconf/ajaxop");
+        let uri = self.get_url("/mobileconf/ajaxop");
         let response = self.client.get(&uri)
             .header("X-Requested-With", "com.valvesoftware.android.steam.community")
             .query(&query)
@@ -222,9 +219,10 @@ impl MobileAPI {
     }
     
     fn get_url(
+        &self,
         pathname: &str,
     ) -> String {
-        format!("https://{}{pathname}", Self::HOSTNAME)
+        join_url(&self.community_url, pathname)
     }
 }
 
@@ -236,6 +234,9 @@ impl From<MobileAPIBuilder> for MobileAPI {
             .unwrap_or_else(|| get_default_middleware(
                 Arc::clone(&cookies),
                 builder.user_agent,
+                builder.rate_limiter.map(|rate_limiter| rate_limiter
+                    .community_url(builder.community_url.clone())),
+                builder.retry_policy,
             ));
         
         Self {
@@ -245,6 +246,7 @@ impl From<MobileAPIBuilder> for MobileAPI {
             identity_secret: builder.identity_secret,
             steamid: Arc::new(AtomicU64::new(0)),
             time_offset: builder.time_offset,
+            community_url: builder.community_url,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,384 @@
+use crate::helpers::{DEFAULT_COMMUNITY_URL, DEFAULT_WEB_API_URL};
+use std::sync::Arc;
+use std::time::Duration;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use task_local_extensions::Extensions;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use url::Url;
+
+/// A budget of `requests` requests for every `per` duration.
+/// 
+/// Budgets are enforced using a token bucket which holds up to `requests` tokens and refills
+/// evenly over `per`. This allows short bursts of up to `requests` requests while keeping the
+/// average rate within the budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The number of requests allowed.
+    pub requests: u32,
+    /// The duration the requests are allowed within.
+    pub per: Duration,
+}
+
+impl RateLimit {
+    /// Creates a new [`RateLimit`] allowing `requests` requests for every `per` duration.
+    pub fn new(requests: u32, per: Duration) -> Self {
+        Self {
+            requests,
+            per,
+        }
+    }
+}
+
+/// The groups of endpoints which have their own budgets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Endpoint {
+    /// Inventories on Steam Community.
+    Inventory,
+    /// All other endpoints on Steam Community, e.g. sending and responding to offers.
+    Community,
+    /// Endpoints on the Steam Web API.
+    WebApi,
+}
+
+impl Endpoint {
+    /// Gets the endpoint group for a URL using the base URLs requests are sent to. Returns `None` 
+    /// for URLs which are not rate limited.
+    fn from_url(
+        url: &Url,
+        community_url: &Url,
+        web_api_url: &Url,
+    ) -> Option<Self> {
+        let community_path = relative_path(url, community_url);
+        let web_api_path = relative_path(url, web_api_url);
+        
+        // When both base URLs match, the one with the longer path is more specific. Community is 
+        // used if they are the same.
+        let is_web_api = match (community_path, web_api_path) {
+            (Some(community_path), Some(web_api_path)) => web_api_path.len() < community_path.len(),
+            (None, Some(_)) => true,
+            _ => false,
+        };
+        
+        if is_web_api {
+            return Some(Self::WebApi);
+        }
+        
+        let path = community_path?;
+        
+        if path.starts_with("/inventory/") || path.contains("/inventory/json/") {
+            Some(Self::Inventory)
+        } else {
+            Some(Self::Community)
+        }
+    }
+}
+
+/// Gets the path of `url` relative to `base_url`. Returns `None` if `url` is not under 
+/// `base_url`.
+fn relative_path<'a>(
+    url: &'a Url,
+    base_url: &Url,
+) -> Option<&'a str> {
+    if
+        url.scheme() != base_url.scheme() ||
+        url.host_str() != base_url.host_str() ||
+        url.port_or_known_default() != base_url.port_or_known_default()
+    {
+        return None;
+    }
+    
+    let path = url.path().strip_prefix(base_url.path().trim_end_matches('/'))?;
+    
+    if path.is_empty() || path.starts_with('/') {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// A token bucket.
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    state: Mutex<BucketState>,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            state: Mutex::new(BucketState {
+                tokens: limit.requests as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+    
+    /// Waits until a token is available then takes it.
+    async fn acquire(&self) {
+        let capacity = self.limit.requests.max(1) as f64;
+        let seconds_per_token = self.limit.per.as_secs_f64() / capacity;
+        // The lock is held while waiting so that requests are let through in the order they
+        // arrived.
+        let mut state = self.state.lock().await;
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        
+        if seconds_per_token > 0.0 {
+            state.tokens = (state.tokens + elapsed / seconds_per_token).min(capacity);
+        } else {
+            state.tokens = capacity;
+        }
+        
+        state.last_refill = now;
+        
+        if state.tokens < 1.0 {
+            let wait = Duration::from_secs_f64((1.0 - state.tokens) * seconds_per_token);
+            
+            tokio::time::sleep(wait).await;
+            state.tokens = 1.0;
+            state.last_refill = Instant::now();
+        }
+        
+        state.tokens -= 1.0;
+    }
+}
+
+#[derive(Debug)]
+struct Buckets {
+    inventory: Option<Bucket>,
+    community: Option<Bucket>,
+    web_api: Option<Bucket>,
+}
+
+/// Limits the rate of requests sent to Steam.
+/// 
+/// Requests to inventories on `steamcommunity.com`, all other endpoints on `steamcommunity.com`
+/// (such as sending and responding to trade offers), and `api.steampowered.com` each have their
+/// own budget. Requests to other hosts are not limited. When a budget is exhausted, requests
+/// wait until the budget allows them to be sent.
+/// 
+/// The limiter is installed as middleware on the default client created by the builders, which 
+/// set the base URLs it matches requests against to their own `community_url` and `web_api_url`. 
+/// Internally the state is wrapped in an `Arc`, so a [`RateLimiter`] can be cloned and shared
+/// between multiple instances of [`TradeOfferManager`][crate::TradeOfferManager] that use the
+/// same account or IP address. Setting a limiter on a builder has no effect when a client is 
+/// provided using `client`; add the limiter to the client using 
+/// [`ClientBuilder::with`][reqwest_middleware::ClientBuilder::with] instead.
+/// 
+/// # Examples
+/// ```
+/// use steam_tradeoffer_manager::{TradeOfferManager, RateLimiter, RateLimit};
+/// use std::time::Duration;
+/// 
+/// let rate_limiter = RateLimiter::new()
+///     .inventory(RateLimit::new(5, Duration::from_secs(60)));
+/// let builder = TradeOfferManager::builder()
+///     .rate_limiter(rate_limiter.clone());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    inner: Arc<Buckets>,
+    community_url: Url,
+    web_api_url: Url,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RateLimiter {
+    /// The default budget for inventories on `steamcommunity.com`.
+    pub const DEFAULT_INVENTORY_LIMIT: RateLimit = RateLimit {
+        requests: 10,
+        per: Duration::from_secs(60),
+    };
+    /// The default budget for other endpoints on `steamcommunity.com`.
+    pub const DEFAULT_COMMUNITY_LIMIT: RateLimit = RateLimit {
+        requests: 60,
+        per: Duration::from_secs(60),
+    };
+    /// The default budget for `api.steampowered.com`.
+    pub const DEFAULT_WEB_API_LIMIT: RateLimit = RateLimit {
+        requests: 60,
+        per: Duration::from_secs(60),
+    };
+    
+    /// Creates a new [`RateLimiter`] using the default budgets.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Buckets {
+                inventory: Some(Bucket::new(Self::DEFAULT_INVENTORY_LIMIT)),
+                community: Some(Bucket::new(Self::DEFAULT_COMMUNITY_LIMIT)),
+                web_api: Some(Bucket::new(Self::DEFAULT_WEB_API_LIMIT)),
+            }),
+            community_url: DEFAULT_COMMUNITY_URL.clone(),
+            web_api_url: DEFAULT_WEB_API_URL.clone(),
+        }
+    }
+    
+    /// The base URL for Steam Community requests, e.g. when requests are sent through a proxy. 
+    /// Default is `https://steamcommunity.com`. Clones made before this is set share their 
+    /// budgets with the returned limiter.
+    pub fn community_url(mut self, community_url: Url) -> Self {
+        self.community_url = community_url;
+        self
+    }
+    
+    /// The base URL for Steam Web API requests, e.g. when requests are sent through a proxy. 
+    /// Default is `https://api.steampowered.com`. Clones made before this is set share their 
+    /// budgets with the returned limiter.
+    pub fn web_api_url(mut self, web_api_url: Url) -> Self {
+        self.web_api_url = web_api_url;
+        self
+    }
+    
+    /// The budget for inventories on `steamcommunity.com`. Default is
+    /// [`RateLimiter::DEFAULT_INVENTORY_LIMIT`].
+    pub fn inventory(self, limit: RateLimit) -> Self {
+        self.with_buckets(|buckets| buckets.inventory = Some(Bucket::new(limit)))
+    }
+    
+    /// The budget for other endpoints on `steamcommunity.com`, such as sending and responding to
+    /// trade offers. Default is [`RateLimiter::DEFAULT_COMMUNITY_LIMIT`].
+    pub fn community(self, limit: RateLimit) -> Self {
+        self.with_buckets(|buckets| buckets.community = Some(Bucket::new(limit)))
+    }
+    
+    /// The budget for `api.steampowered.com`. Default is
+    /// [`RateLimiter::DEFAULT_WEB_API_LIMIT`].
+    pub fn web_api(self, limit: RateLimit) -> Self {
+        self.with_buckets(|buckets| buckets.web_api = Some(Bucket::new(limit)))
+    }
+    
+    /// Removes the budget for inventories on `steamcommunity.com`.
+    pub fn unlimited_inventory(self) -> Self {
+        self.with_buckets(|buckets| buckets.inventory = None)
+    }
+    
+    /// Removes the budget for other endpoints on `steamcommunity.com`.
+    pub fn unlimited_community(self) -> Self {
+        self.with_buckets(|buckets| buckets.community = None)
+    }
+    
+    /// Removes the budget for `api.steampowered.com`.
+    pub fn unlimited_web_api(self) -> Self {
+        self.with_buckets(|buckets| buckets.web_api = None)
+    }
+    
+    /// Waits until a request to `url` is allowed by its budget.
+    pub async fn acquire(&self, url: &Url) {
+        let bucket = match Endpoint::from_url(url, &self.community_url, &self.web_api_url) {
+            Some(Endpoint::Inventory) => self.inner.inventory.as_ref(),
+            Some(Endpoint::Community) => self.inner.community.as_ref(),
+            Some(Endpoint::WebApi) => self.inner.web_api.as_ref(),
+            None => None,
+        };
+        
+        if let Some(bucket) = bucket {
+            bucket.acquire().await;
+        }
+    }
+    
+    fn with_buckets<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut Buckets),
+    {
+        let mut buckets = match Arc::try_unwrap(self.inner) {
+            Ok(buckets) => buckets,
+            // The limiter was already shared. Copy the budgets into new buckets so that the
+            // clones are not affected.
+            Err(inner) => Buckets {
+                inventory: inner.inventory.as_ref().map(|bucket| Bucket::new(bucket.limit)),
+                community: inner.community.as_ref().map(|bucket| Bucket::new(bucket.limit)),
+                web_api: inner.web_api.as_ref().map(|bucket| Bucket::new(bucket.limit)),
+            },
+        };
+        
+        f(&mut buckets);
+        
+        Self {
+            inner: Arc::new(buckets),
+            community_url: self.community_url,
+            web_api_url: self.web_api_url,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for RateLimiter {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        self.acquire(req.url()).await;
+        next.run(req, extensions).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn classifies_endpoints() {
+        let endpoint = |url: &str| Endpoint::from_url(
+            &url.parse::<Url>().unwrap(),
+            &DEFAULT_COMMUNITY_URL,
+            &DEFAULT_WEB_API_URL,
+        );
+        
+        assert_eq!(endpoint("https://steamcommunity.com/inventory/76561197960287930/440/2"), Some(Endpoint::Inventory));
+        assert_eq!(endpoint("https://steamcommunity.com/profiles/76561197960287930/inventory/json/440/2"), Some(Endpoint::Inventory));
+        assert_eq!(endpoint("https://steamcommunity.com/tradeoffer/new/send"), Some(Endpoint::Community));
+        assert_eq!(endpoint("https://api.steampowered.com/IEconService/GetTradeOffers/v1"), Some(Endpoint::WebApi));
+        assert_eq!(endpoint("https://example.com/inventory/"), None);
+    }
+    
+    #[test]
+    fn classifies_endpoints_using_base_urls() {
+        let community_url = "http://127.0.0.1:8080/steam/".parse::<Url>().unwrap();
+        let web_api_url = "http://127.0.0.1:8080/steam/api".parse::<Url>().unwrap();
+        let endpoint = |url: &str| Endpoint::from_url(
+            &url.parse::<Url>().unwrap(),
+            &community_url,
+            &web_api_url,
+        );
+        
+        assert_eq!(endpoint("http://127.0.0.1:8080/steam/inventory/76561197960287930/440/2"), Some(Endpoint::Inventory));
+        assert_eq!(endpoint("http://127.0.0.1:8080/steam/tradeoffer/new/send"), Some(Endpoint::Community));
+        assert_eq!(endpoint("http://127.0.0.1:8080/steam/api/IEconService/GetTradeOffers/v1"), Some(Endpoint::WebApi));
+        assert_eq!(endpoint("http://127.0.0.1:8080/steamy/tradeoffer/new/send"), None);
+        assert_eq!(endpoint("http://127.0.0.1:8081/steam/tradeoffer/new/send"), None);
+        assert_eq!(endpoint("https://steamcommunity.com/tradeoffer/new/send"), None);
+    }
+    
+    #[tokio::test]
+    async fn waits_when_budget_is_exhausted() {
+        let rate_limiter = RateLimiter::new()
+            .web_api(RateLimit::new(2, Duration::from_millis(200)));
+        let url = "https://api.steampowered.com/IEconService/GetTradeOffers/v1"
+            .parse::<Url>()
+            .unwrap();
+        let started = Instant::now();
+        
+        rate_limiter.acquire(&url).await;
+        rate_limiter.acquire(&url).await;
+        assert!(started.elapsed() < Duration::from_millis(50));
+        
+        rate_limiter.acquire(&url).await;
+        assert!(started.elapsed() >= Duration::from_millis(90));
+    }
+}
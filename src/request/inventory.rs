@@ -1,13 +1,16 @@
 use crate::SteamID;
 use crate::enums::Language;
 use crate::types::{AppId, ContextId, Client};
-use crate::helpers::DEFAULT_CLIENT;
+use crate::helpers::{DEFAULT_CLIENT, DEFAULT_COMMUNITY_URL};
+use url::Url;
 
 /// Options for loading a user's inventory.
 #[derive(Debug, Clone)]
 pub struct GetInventoryOptions<'a> {
     /// Client to use for making requests.
     pub client: &'a Client,
+    /// The base URL for Steam Community requests, e.g. `https://steamcommunity.com`.
+    pub community_url: &'a Url,
     /// The user's Steam ID.
     pub steamid: SteamID,
     /// App ID of inventory.
@@ -30,6 +33,7 @@ impl<'a> GetInventoryOptions<'a> {
     ) -> GetInventoryOptions<'a> {
         Self {
             client: &DEFAULT_CLIENT,
+            community_url: &DEFAULT_COMMUNITY_URL,
             steamid,
             appid,
             contextid,
@@ -1,6 +1,7 @@
 use crate::types::{AppId, ClassId, InstanceId};
 use crate::serialize;
 use crate::types::ServerTime;
+use crate::time::get_server_time_now;
 use serde::{Serialize, Deserialize};
 
 /// Contains details about an item including names and descriptions.
@@ -94,6 +95,14 @@ pub struct ClassInfo {
 }
 
 impl ClassInfo {
+    /// Checks whether this classinfo is past its `cache_expiration`. Expired classinfos are 
+    /// fetched again rather than loaded from caches.
+    pub fn is_cache_expired(&self) -> bool {
+        self.cache_expiration
+            .map(|cache_expiration| cache_expiration <= get_server_time_now())
+            .unwrap_or(false)
+    }
+    
     /// Convenience method for getting a value from `app_data`.
     pub fn get_app_data_value(&self, key: &str) -> Option<&serde_json::Value> {
         if let Some(app_data) = &self.app_data {
@@ -233,6 +242,21 @@ mod tests {
         assert!(classinfo.item_expiration.is_some());
     }
     
+    #[test]
+    fn is_cache_expired_works() {
+        let mut classinfo: super::ClassInfo = serde_json::from_str(include_str!("fixtures/classinfo_item_expiration.json")).unwrap();
+        
+        assert!(classinfo.is_cache_expired());
+        
+        classinfo.cache_expiration = Some(crate::time::get_server_time_now() + chrono::Duration::try_days(1).unwrap());
+        
+        assert!(!classinfo.is_cache_expired());
+        
+        classinfo.cache_expiration = None;
+        
+        assert!(!classinfo.is_cache_expired());
+    }
+    
     #[test]
     fn is_color_works() {
         let classinfo: super::ClassInfo = serde_json::from_str(include_str!("fixtures/classinfo_item_expiration.json")).unwrap();
@@ -48,6 +48,11 @@ pub struct TradeOffer {
     pub escrow_end_date: Option<ServerTime>,
     /// The confirmation method for this offer.
     pub confirmation_method: ConfirmationMethod,
+    /// Custom data attached to this offer using 
+    /// [`TradeOfferManager::set_offer_data`][crate::TradeOfferManager::set_offer_data]. Only 
+    /// included on offers emitted from polls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
 }
 
 impl Default for TradeOffer {
@@ -67,6 +72,7 @@ impl Default for TradeOffer {
             trade_offer_state: TradeOfferState::Active,
             escrow_end_date: None,
             confirmation_method: ConfirmationMethod::None,
+            data: None,
         }
     }
 }
@@ -0,0 +1,274 @@
+use crate::api::response::{RawTradeOffer, RawAsset};
+use crate::enums::{TradeOfferState, ConfirmationMethod};
+use crate::request::{NewTradeOffer, NewTradeOfferItem};
+use crate::response::SentOffer;
+use crate::types::{AppId, ContextId, AssetId, Amount, ServerTime, TradeOfferId};
+use std::collections::HashSet;
+use std::time::Duration;
+use reqwest::{Request, Response, StatusCode};
+use reqwest_middleware::{Middleware, Next};
+use task_local_extensions::Extensions;
+
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_DELAY_SECONDS: u64 = 1;
+const DEFAULT_MAX_DELAY_SECONDS: u64 = 30;
+
+/// Retries requests which fail due to transient errors, such as timeouts, connection errors,
+/// `429 Too Many Requests` and `5xx` responses.
+/// 
+/// When installed as middleware, only requests which read data (e.g. getting trade offers,
+/// inventories, and classinfos) are retried. Requests which act on trade offers or confirmations 
+/// are never retried by the middleware. [`TradeOfferManager`][crate::TradeOfferManager] uses the policy to retry 
+/// sending and accepting offers, first checking whether the failed attempt took effect so that
+/// an offer is never sent twice.
+/// 
+/// # Examples
+/// ```
+/// use steam_tradeoffer_manager::{TradeOfferManager, RetryPolicy};
+/// 
+/// let builder = TradeOfferManager::builder()
+///     .retry_policy(RetryPolicy {
+///         max_retries: 5,
+///         ..RetryPolicy::default()
+///     });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of times a request is retried. Default is `3`.
+    pub max_retries: u32,
+    /// The delay before the first retry. The delay doubles for each retry after. Default is 1
+    /// second.
+    pub delay: Duration,
+    /// The maximum delay between retries. Default is 30 seconds.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            delay: Duration::from_secs(DEFAULT_DELAY_SECONDS),
+            max_delay: Duration::from_secs(DEFAULT_MAX_DELAY_SECONDS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Gets the delay to wait before the retry following `attempt` failed attempts, where the
+    /// first failed attempt is `0`.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        // Limit the exponent so the value does not overflow.
+        let multiplier = 2u32.saturating_pow(attempt.min(31));
+        
+        self.delay.saturating_mul(multiplier).min(self.max_delay)
+    }
+}
+
+/// Paths of requests which act on confirmations despite using `GET`.
+const CONFIRMATION_ACTION_PATHS: [&str; 2] = [
+    "/mobileconf/ajaxop",
+    "/mobileconf/multiajaxop",
+];
+
+/// Whether a request only reads data, so that it is safe to send again.
+fn is_read_request(req: &Request) -> bool {
+    let path = req.url().path();
+    
+    req.method().is_safe() && !CONFIRMATION_ACTION_PATHS
+        .iter()
+        .any(|action_path| path.ends_with(action_path))
+}
+
+/// Whether a response with this status should be retried.
+pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[async_trait::async_trait]
+impl Middleware for RetryPolicy {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        // Only requests which read data are safe to retry.
+        if !is_read_request(&req) {
+            return next.run(req, extensions).await;
+        }
+        
+        let mut attempt = 0;
+        
+        loop {
+            let retry_req = if attempt < self.max_retries {
+                req.try_clone()
+            } else {
+                None
+            };
+            let Some(attempt_req) = retry_req else {
+                return next.run(req, extensions).await;
+            };
+            let result = next.clone().run(attempt_req, extensions).await;
+            let should_retry = match &result {
+                Ok(response) => is_retryable_status(response.status()),
+                Err(reqwest_middleware::Error::Reqwest(error)) => {
+                    error.is_timeout() || error.is_connect()
+                },
+                Err(_) => false,
+            };
+            
+            if !should_retry {
+                return result;
+            }
+            
+            log::debug!("Retrying request to {} after failed attempt {}", req.url(), attempt + 1);
+            tokio::time::sleep(self.delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+type ItemKey = (AppId, ContextId, AssetId, Amount);
+
+fn sorted_items<I>(items: I) -> Vec<ItemKey>
+where
+    I: Iterator<Item = ItemKey>,
+{
+    let mut items = items.collect::<Vec<_>>();
+    
+    items.sort_unstable();
+    items
+}
+
+fn new_item_keys(items: &[NewTradeOfferItem]) -> Vec<ItemKey> {
+    sorted_items(items.iter().map(|item| (item.appid, item.contextid, item.assetid, item.amount)))
+}
+
+fn raw_item_keys(items: &[RawAsset]) -> Vec<ItemKey> {
+    sorted_items(items.iter().map(|item| (item.appid, item.contextid, item.assetid, item.amount)))
+}
+
+/// Finds an offer in `offers` which matches `offer`, was created at or after `since`, and is not 
+/// in `existing_tradeofferids`. Used for checking whether an offer was sent despite the request 
+/// failing, where `existing_tradeofferids` are the offers which were already sent beforehand.
+pub(crate) fn find_sent_offer<'a>(
+    offers: &'a [RawTradeOffer],
+    offer: &NewTradeOffer,
+    since: ServerTime,
+    existing_tradeofferids: &HashSet<TradeOfferId>,
+) -> Option<&'a RawTradeOffer> {
+    let items_to_give = new_item_keys(&offer.items_to_give);
+    let items_to_receive = new_item_keys(&offer.items_to_receive);
+    
+    offers
+        .iter()
+        .filter(|raw| {
+            raw.is_our_offer &&
+            raw.accountid_other == offer.partner.account_id() &&
+            raw.time_created >= since &&
+            !existing_tradeofferids.contains(&raw.tradeofferid)
+        })
+        .find(|raw| {
+            raw_item_keys(&raw.items_to_give) == items_to_give &&
+            raw_item_keys(&raw.items_to_receive) == items_to_receive
+        })
+}
+
+/// Creates a [`SentOffer`] from an offer found after sending it failed.
+pub(crate) fn sent_offer_from_raw(
+    raw: &RawTradeOffer,
+) -> SentOffer {
+    let needs_confirmation = raw.trade_offer_state == TradeOfferState::CreatedNeedsConfirmation;
+    
+    SentOffer {
+        tradeofferid: raw.tradeofferid,
+        needs_mobile_confirmation: needs_confirmation && raw.confirmation_method != ConfirmationMethod::Email,
+        needs_email_confirmation: needs_confirmation && raw.confirmation_method == ConfirmationMethod::Email,
+        email_domain: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SteamID;
+    use chrono::Duration as ChronoDuration;
+    
+    fn raw_asset(assetid: AssetId) -> RawAsset {
+        RawAsset {
+            appid: 440,
+            contextid: 2,
+            assetid,
+            amount: 1,
+            missing: false,
+            classid: 1,
+            instanceid: None,
+        }
+    }
+    
+    fn sent_raw_offer(
+        accountid_other: u32,
+        assetid: AssetId,
+        time_created: ServerTime,
+    ) -> RawTradeOffer {
+        RawTradeOffer {
+            tradeofferid: assetid,
+            tradeid: None,
+            accountid_other,
+            message: None,
+            items_to_receive: Vec::new(),
+            items_to_give: vec![raw_asset(assetid)],
+            is_our_offer: true,
+            from_real_time_trade: false,
+            expiration_time: time_created,
+            time_created,
+            time_updated: time_created,
+            trade_offer_state: TradeOfferState::CreatedNeedsConfirmation,
+            escrow_end_date: None,
+            confirmation_method: ConfirmationMethod::MobileApp,
+        }
+    }
+    
+    #[test]
+    fn doubles_delay_up_to_max_delay() {
+        let policy = RetryPolicy::default();
+        
+        assert_eq!(policy.delay(0).as_secs(), 1);
+        assert_eq!(policy.delay(2).as_secs(), 4);
+        assert_eq!(policy.delay(100).as_secs(), 30);
+    }
+    
+    #[test]
+    fn finds_sent_offer_matching_partner_and_items() {
+        let partner = SteamID::from(76561198080179568);
+        let now = crate::time::get_server_time_now();
+        let since = now - ChronoDuration::try_minutes(1).unwrap();
+        let offer = NewTradeOffer::builder(partner)
+            .items_to_give(vec![NewTradeOfferItem {
+                appid: 440,
+                contextid: 2,
+                assetid: 3,
+                amount: 1,
+            }])
+            .build();
+        let offers = vec![
+            // Different partner.
+            sent_raw_offer(1, 3, now),
+            // Different items.
+            sent_raw_offer(partner.account_id(), 4, now),
+            // Created before the offer was sent.
+            sent_raw_offer(partner.account_id(), 3, now - ChronoDuration::try_minutes(5).unwrap()),
+            sent_raw_offer(partner.account_id(), 3, now),
+        ];
+        let found = find_sent_offer(&offers, &offer, since, &HashSet::new()).unwrap();
+        
+        assert!(std::ptr::eq(found, &offers[3]));
+        assert!(sent_offer_from_raw(found).needs_mobile_confirmation);
+        assert!(find_sent_offer(&offers[..3], &offer, since, &HashSet::new()).is_none());
+        
+        // An identical offer which was sent before the failed attempt.
+        let existing_tradeofferids = HashSet::from([offers[3].tradeofferid]);
+        
+        assert!(find_sent_offer(&offers, &offer, since, &existing_tradeofferids).is_none());
+    }
+}
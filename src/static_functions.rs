@@ -4,8 +4,7 @@ use crate::api::response as api_response;
 use crate::response::{Asset, ClassInfo};
 use crate::request::GetInventoryOptions;
 use crate::types::*;
-use crate::helpers::{parses_response, get_sessionid_and_steamid_from_cookies};
-use crate::helpers::COMMUNITY_HOSTNAME;
+use crate::helpers::{parses_response, get_sessionid_and_steamid_from_cookies, join_url};
 use crate::error::{Error, ParseHtmlError, MissingClassInfoError};
 use crate::serialize;
 use std::collections::HashMap;
@@ -20,21 +19,19 @@ const ERROR_COULD_NOT_GET_API_KEY: &str = "API key could not be parsed from resp
 const ERROR_NO_API_KEY: &str = "This account does not have an API key";
 
 /// A stand-alone method for getting a user's inventory. Optionally allows specifying a client to 
-/// use for requests (useful if you need to proxy your requests, for example).
+/// use for requests (useful if you need to proxy your requests, for example) and the base URL 
+/// requests are sent to.
 /// 
 /// # Examples
 /// ```no_run
-/// use steam_tradeoffer_manager::get_inventory;
+/// use steam_tradeoffer_manager::{get_inventory, SteamID};
 /// use steam_tradeoffer_manager::request::GetInventoryOptions;
 /// 
 /// #[tokio::main]
 /// async fn main() {
 ///     let options = GetInventoryOptions {
-///         steamid: SteamID::from(76561199436464454),
-///         appid: 730,
-///         contextid: 2,
 ///         tradable_only: false,
-///         ..Default::default()
+///         ..GetInventoryOptions::new(SteamID::from(76561199436464454), 730, 2)
 ///     };
 ///     let inventory = get_inventory(&options).await.unwrap();
 ///     
@@ -56,8 +53,8 @@ pub async fn get_inventory<'a>(
     let sid = u64::from(options.steamid);
     let appid = options.appid;
     let contextid = options.contextid;
-    let uri = format!("https://{COMMUNITY_HOSTNAME}/inventory/{sid}/{appid}/{contextid}");
-    let referer = format!("https://{COMMUNITY_HOSTNAME}/profiles/{sid}/inventory");
+    let uri = join_url(options.community_url, &format!("/inventory/{sid}/{appid}/{contextid}"));
+    let referer = join_url(options.community_url, &format!("/profiles/{sid}/inventory"));
     
     loop {
         let response = options.client.get(&uri)
@@ -128,9 +125,11 @@ pub async fn get_inventory<'a>(
 /// 
 /// This method requires your cookies. If your account does not have an API key set, one will be 
 /// created using `localhost` as the domain. By calling this method you are agreeing to the 
-/// [Steam Web API Terms of Use](https://steamcommunity.com/dev/apiterms).
+/// [Steam Web API Terms of Use](https://steamcommunity.com/dev/apiterms). Requests are sent to 
+/// `community_url`.
 pub async fn get_api_key(
     cookies: &[String],
+    community_url: &Url,
 ) -> Result<String, Error> {
     #[derive(Serialize)]
     #[serde(rename_all = "camelCase")]
@@ -148,22 +147,19 @@ pub async fn get_api_key(
     let sessionid = sessionid
         .ok_or(Error::NotLoggedIn)?;
     let cookie_store = Arc::new(Jar::default());
-    // Should not panic since the URL is hardcoded.
-    let url = format!("https://{COMMUNITY_HOSTNAME}").parse::<Url>()
-        .unwrap_or_else(|error| panic!("URL could not be parsed from {COMMUNITY_HOSTNAME}: {error}"));
     
     for cookie in cookies {
-        cookie_store.add_cookie_str(cookie, &url);
+        cookie_store.add_cookie_str(cookie, community_url);
     }
     
     let client = reqwest::ClientBuilder::new()
         .cookie_provider(cookie_store)
         .build()?;
     
-    match try_get_key(&client).await {
+    match try_get_key(&client, community_url).await {
         Ok(api_key) => Ok(api_key),
         Err(Error::ParseHtml(ParseHtmlError::Malformed(message))) if message == ERROR_NO_API_KEY => {
-            let uri = format!("https://{COMMUNITY_HOSTNAME}/dev/registerkey");
+            let uri = join_url(community_url, "/dev/registerkey");
             let _response = client.post(uri)
                 .form(&CreateAPIKey {
                     domain: "localhost".into(),
@@ -174,15 +170,18 @@ pub async fn get_api_key(
                 .send()
                 .await?;
             
-            try_get_key(&client).await
+            try_get_key(&client, community_url).await
         },
         Err(error) => Err(error),
     }
 }
 
-/// Makes a request to https://steamcommunity.com/dev/apikey and scrapes the page for the API key.
-async fn try_get_key(client: &reqwest::Client) -> Result<String, Error> {
-    let uri = format!("https://{COMMUNITY_HOSTNAME}/dev/apikey");
+/// Makes a request to `/dev/apikey` on `community_url` and scrapes the page for the API key.
+async fn try_get_key(
+    client: &reqwest::Client,
+    community_url: &Url,
+) -> Result<String, Error> {
+    let uri = join_url(community_url, "/dev/apikey");
     let response = client.get(uri)
         .send()
         .await?;
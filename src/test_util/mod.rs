@@ -0,0 +1,450 @@
+//! An offline fake of the Steam Community and Steam Web API endpoints used by this crate.
+//! Requires the `test-util` feature.
+//! 
+//! [`FakeSteam`] runs a local HTTP server which serves trade offers, inventories, classinfos, and
+//! mobile confirmations from an in-memory [`FakeSteamState`]. Point a [`TradeOfferManager`] at it
+//! using [`FakeSteam::manager_builder`] to test code built on this crate without a Steam account
+//! or network access. Failures can be scripted using [`FakeSteamState::fail_next`] and
+//! [`FakeSteamState::fail_next_after_applying`].
+//! 
+//! # Examples
+//! ```
+//! use steam_tradeoffer_manager::SteamID;
+//! use steam_tradeoffer_manager::request::NewTradeOffer;
+//! use steam_tradeoffer_manager::test_util::{FakeSteam, fake_classinfo};
+//! 
+//! #[tokio::main]
+//! async fn main() {
+//!     let steamid = SteamID::from(76561198000000001);
+//!     let partner = SteamID::from(76561198000000002);
+//!     let steam = FakeSteam::start(steamid).unwrap();
+//!     let asset = steam.state().add_item(steamid, 440, 2, fake_classinfo(101, "Mann Co. Supply Crate Key"));
+//!     let manager = steam.manager_builder().build();
+//!     let offer = NewTradeOffer::builder(partner)
+//!         .items_to_give(vec![asset])
+//!         .build();
+//!     let sent_offer = manager.send_offer(&offer).await.unwrap();
+//! 
+//!     assert!(steam.state().offer(sent_offer.tradeofferid).is_some());
+//! }
+//! ```
+
+mod state;
+
+pub use state::{
+    FakeSteamState,
+    FakeRoute,
+    FakeFailure,
+    FakeRequest,
+    FakeConfirmation,
+    FAKE_API_KEY,
+};
+
+use crate::{SteamID, TradeOfferManager, TradeOfferManagerBuilder};
+use crate::polling::MemoryPollDataStore;
+use crate::response::ClassInfo;
+use crate::types::ClassId;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex, MutexGuard};
+use hyper::{Body, Request, Response};
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use tokio::sync::oneshot;
+use url::Url;
+
+/// An identity secret accepted by [`FakeSteam`]. Confirmation keys are not verified.
+pub const FAKE_IDENTITY_SECRET: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+
+/// A local HTTP server faking Steam. The server is shut down when this is dropped.
+#[derive(Debug)]
+pub struct FakeSteam {
+    addr: SocketAddr,
+    url: Url,
+    state: Arc<Mutex<FakeSteamState>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl FakeSteam {
+    /// Starts a server on a random local port for the account `steamid`. Must be called within a
+    /// Tokio runtime.
+    pub fn start(steamid: SteamID) -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        
+        listener.set_nonblocking(true)?;
+        
+        let addr = listener.local_addr()?;
+        let url = format!("http://{addr}")
+            .parse::<Url>()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        let state = Arc::new(Mutex::new(FakeSteamState::new(steamid)));
+        let service_state = Arc::clone(&state);
+        let make_service = make_service_fn(move |_conn| {
+            let state = Arc::clone(&service_state);
+            
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    handle_request(Arc::clone(&state), req)
+                }))
+            }
+        });
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+        let server = hyper::Server::from_tcp(listener)
+            .map_err(io::Error::other)?
+            .serve(make_service)
+            .with_graceful_shutdown(async {
+                shutdown_rx.await.ok();
+            });
+        
+        tokio::spawn(async move {
+            if let Err(error) = server.await {
+                log::warn!("Fake Steam server error: {error}");
+            }
+        });
+        
+        Ok(Self {
+            addr,
+            url,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+    
+    /// The address the server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+    
+    /// The base URL of the server, used for both Steam Community and Steam Web API requests.
+    pub fn url(&self) -> Url {
+        self.url.clone()
+    }
+    
+    /// Locks the state of the server. Do not hold the guard across requests to the server.
+    pub fn state(&self) -> MutexGuard<'_, FakeSteamState> {
+        self.state.lock().unwrap()
+    }
+    
+    /// Cookies for the logged in account.
+    pub fn cookies(&self) -> Vec<String> {
+        let steamid = u64::from(self.state().steamid());
+        
+        vec![
+            format!("steamLoginSecure={steamid}%7C%7Cfaketoken"),
+            "sessionid=fakesessionid".to_string(),
+        ]
+    }
+    
+    /// A [`TradeOfferManagerBuilder`] configured to use this server. Poll data is stored in
+    /// memory and classinfos are stored in a temporary directory unique to this server.
+    pub fn manager_builder(&self) -> TradeOfferManagerBuilder {
+        let data_directory = std::env::temp_dir()
+            .join("steam-tradeoffer-manager-fake-steam")
+            .join(self.addr.port().to_string());
+        
+        TradeOfferManager::builder()
+            .api_key(FAKE_API_KEY.into())
+            .identity_secret(FAKE_IDENTITY_SECRET.into())
+            .cookies(self.cookies())
+            .community_url(self.url())
+            .web_api_url(self.url())
+            .data_directory(data_directory)
+            .poll_data_store(MemoryPollDataStore::new())
+    }
+}
+
+impl Drop for FakeSteam {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+/// Creates a [`ClassInfo`] with the given `classid` and `name` and defaults for all other fields.
+pub fn fake_classinfo(classid: ClassId, name: &str) -> ClassInfo {
+    serde_json::from_value(serde_json::json!({
+        "classid": classid.to_string(),
+        "instanceid": "0",
+        "name": name,
+        "market_name": name,
+        "market_hash_name": name,
+        "icon_url": "",
+        "icon_url_large": "",
+        "type": "",
+        "tradable": 1,
+        "marketable": 1,
+        "commodity": 0,
+    }))
+    .expect("fake classinfo is valid")
+}
+
+fn parse_params(input: &[u8]) -> HashMap<String, String> {
+    url::form_urlencoded::parse(input)
+        .into_owned()
+        .collect()
+}
+
+async fn handle_request(
+    state: Arc<Mutex<FakeSteamState>>,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let method = req.method().as_str().to_string();
+    let path = req.uri().path().to_string();
+    let query = parse_params(req.uri().query().unwrap_or_default().as_bytes());
+    let body = hyper::body::to_bytes(req.into_body()).await.unwrap_or_default();
+    let form = parse_params(&body);
+    let response = state.lock().unwrap().handle(&method, &path, &query, &form);
+    let response = Response::builder()
+        .status(response.status)
+        .header(CONTENT_TYPE, response.content_type)
+        .body(Body::from(response.body))
+        .unwrap_or_default();
+    
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassInfoCache, RetryPolicy};
+    use crate::classinfo_store::{AppendOnlyClassInfoStore, ClassInfoStore};
+    use crate::api::response::RawTradeOffer;
+    use crate::enums::{Language, TradeOfferState};
+    use crate::polling::{PollAction, PollEvent, PollOptions, PollType};
+    use crate::request::NewTradeOffer;
+    use std::time::Duration;
+    
+    const STEAMID: u64 = 76561198000000001;
+    const PARTNER: u64 = 76561198000000002;
+    
+    #[test]
+    fn raw_trade_offer_round_trips() {
+        let mut state = FakeSteamState::new(SteamID::from(STEAMID));
+        let asset = state.add_item(SteamID::from(PARTNER), 440, 2, fake_classinfo(101, "Key"));
+        let tradeofferid = state.receive_offer(SteamID::from(PARTNER), Vec::new(), vec![asset]);
+        let offer = state.offer(tradeofferid).unwrap();
+        let json = state::offer_json(offer).to_string();
+        let parsed = serde_json::from_str::<RawTradeOffer>(&json).unwrap();
+        
+        assert_eq!(parsed.tradeofferid, tradeofferid);
+        assert_eq!(parsed.items_to_receive[0].assetid, asset.assetid);
+        assert_eq!(parsed.trade_offer_state, TradeOfferState::Active);
+    }
+    
+    #[tokio::test]
+    async fn accepts_received_offer_and_moves_items() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let tradeofferid = {
+            let mut state = steam.state();
+            let asset = state.add_item(partner, 440, 2, fake_classinfo(101, "Key"));
+            
+            state.receive_offer(partner, Vec::new(), vec![asset])
+        };
+        let manager = steam.manager_builder().build();
+        let (mut offers, _) = manager.get_active_trade_offers().await.unwrap();
+        
+        assert_eq!(offers.len(), 1);
+        assert_eq!(offers[0].items_to_receive[0].classinfo.name, "Key");
+        
+        let accepted = manager.accept_offer(&mut offers[0]).await.unwrap();
+        
+        assert!(!accepted.needs_mobile_confirmation);
+        
+        let state = steam.state();
+        
+        assert_eq!(state.offer(tradeofferid).unwrap().trade_offer_state, TradeOfferState::Accepted);
+        assert_eq!(state.inventory(steamid, 440, 2).len(), 1);
+        assert!(state.inventory(partner, 440, 2).is_empty());
+    }
+    
+    #[tokio::test]
+    async fn confirms_sent_offer_during_poll() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let asset = {
+            let mut state = steam.state();
+            
+            state.set_mobile_confirmation_required(true);
+            state.add_item(steamid, 440, 2, fake_classinfo(101, "Key"))
+        };
+        let manager = steam.manager_builder().build();
+        let offer = NewTradeOffer::builder(partner)
+            .items_to_give(vec![asset])
+            .build();
+        let sent_offer = manager.send_offer(&offer).await.unwrap();
+        
+        assert!(sent_offer.needs_mobile_confirmation);
+        assert_eq!(steam.state().confirmations().len(), 1);
+        
+        let (tx, mut rx) = manager.start_polling(PollOptions {
+            confirm_sent_offers: true,
+            poll_interval: chrono::Duration::try_hours(1).unwrap(),
+            ..PollOptions::default()
+        }).unwrap();
+        
+        tx.send(PollAction::DoPoll(PollType::Auto)).await.unwrap();
+        
+        let confirmed = tokio::time::timeout(Duration::from_secs(10), async {
+            while let Ok(event) = rx.recv().await {
+                if let PollEvent::SentOfferConfirmed(tradeofferid) = event {
+                    return Some(tradeofferid);
+                }
+            }
+            
+            None
+        }).await.unwrap();
+        
+        assert_eq!(confirmed, Some(sent_offer.tradeofferid));
+        assert_eq!(
+            steam.state().offer(sent_offer.tradeofferid).unwrap().trade_offer_state,
+            TradeOfferState::Active,
+        );
+        manager.stop_polling();
+    }
+    
+    #[tokio::test]
+    async fn retries_send_without_duplicating_offer() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let asset = {
+            let mut state = steam.state();
+            
+            state.fail_next_after_applying(FakeRoute::SendOffer, FakeFailure::TradeOfferError(16));
+            state.add_item(steamid, 440, 2, fake_classinfo(101, "Key"))
+        };
+        let manager = steam.manager_builder()
+            .retry_policy(RetryPolicy {
+                delay: Duration::from_millis(10),
+                ..RetryPolicy::default()
+            })
+            .build();
+        let offer = NewTradeOffer::builder(partner)
+            .items_to_give(vec![asset])
+            .build();
+        let sent_offer = manager.send_offer(&offer).await.unwrap();
+        let state = steam.state();
+        
+        assert_eq!(state.request_count(FakeRoute::SendOffer), 1);
+        assert_eq!(state.offers().len(), 1);
+        assert_eq!(state.offers()[0].tradeofferid, sent_offer.tradeofferid);
+    }
+    
+    #[tokio::test]
+    async fn retries_send_when_identical_offer_was_already_sent() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let asset = steam.state().add_item(steamid, 440, 2, fake_classinfo(101, "Key"));
+        let manager = steam.manager_builder()
+            .retry_policy(RetryPolicy {
+                delay: Duration::from_millis(10),
+                ..RetryPolicy::default()
+            })
+            .build();
+        let offer = NewTradeOffer::builder(partner)
+            .items_to_give(vec![asset])
+            .build();
+        let earlier_offer = manager.send_offer(&offer).await.unwrap();
+        
+        steam.state().fail_next(FakeRoute::SendOffer, FakeFailure::Status(500));
+        
+        let sent_offer = manager.send_offer(&offer).await.unwrap();
+        let state = steam.state();
+        
+        assert_ne!(sent_offer.tradeofferid, earlier_offer.tradeofferid);
+        assert_eq!(state.request_count(FakeRoute::SendOffer), 3);
+        assert_eq!(state.offers().len(), 2);
+    }
+    
+    #[tokio::test]
+    async fn does_not_retry_confirmation_actions() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let asset = {
+            let mut state = steam.state();
+            
+            state.set_mobile_confirmation_required(true);
+            state.add_item(steamid, 440, 2, fake_classinfo(101, "Key"))
+        };
+        let manager = steam.manager_builder()
+            .retry_policy(RetryPolicy {
+                delay: Duration::from_millis(10),
+                ..RetryPolicy::default()
+            })
+            .build();
+        let offer = NewTradeOffer::builder(partner)
+            .items_to_give(vec![asset])
+            .build();
+        let sent_offer = manager.send_offer(&offer).await.unwrap();
+        
+        steam.state().fail_next_after_applying(FakeRoute::SendConfirmation, FakeFailure::Status(500));
+        
+        assert!(manager.confirm_offer_id(sent_offer.tradeofferid).await.is_err());
+        
+        let state = steam.state();
+        
+        assert_eq!(state.request_count(FakeRoute::SendConfirmation), 1);
+        assert_eq!(state.offer(sent_offer.tradeofferid).unwrap().trade_offer_state, TradeOfferState::Active);
+    }
+    
+    #[tokio::test]
+    async fn fetches_classinfos_saved_before_invalidation_from_steam() {
+        let steamid = SteamID::from(STEAMID);
+        let partner = SteamID::from(PARTNER);
+        let steam = FakeSteam::start(steamid).unwrap();
+        let class = {
+            let mut state = steam.state();
+            let asset = state.add_item(partner, 440, 2, fake_classinfo(101, "Key"));
+            
+            state.receive_offer(partner, Vec::new(), vec![asset]);
+            (asset.appid, asset.classid, asset.instanceid)
+        };
+        let filepath = std::env::temp_dir()
+            .join(format!("steam-tradeoffer-manager-invalidation-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&filepath);
+        let classinfo_store = AppendOnlyClassInfoStore::new(&filepath);
+        let classinfo_cache = ClassInfoCache::default();
+        let manager = steam.manager_builder()
+            .classinfo_cache(classinfo_cache.clone())
+            .classinfo_store(classinfo_store.clone())
+            .build();
+        
+        manager.get_active_trade_offers().await.unwrap();
+        // Classinfos are saved to the store in the background.
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while classinfo_store.load(Language::English, &[class]).await.unwrap().is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+        classinfo_cache.invalidate_app(440);
+        
+        let (offers, _) = manager.get_active_trade_offers().await.unwrap();
+        
+        assert_eq!(offers[0].items_to_receive[0].classinfo.name, "Key");
+        // The classinfo saved before the invalidation is not used.
+        assert_eq!(steam.state().request_count(FakeRoute::GetAssetClassInfo), 2);
+        let _ = std::fs::remove_file(&filepath);
+    }
+    
+    #[tokio::test]
+    async fn rejects_requests_without_api_key() {
+        let steam = FakeSteam::start(SteamID::from(STEAMID)).unwrap();
+        let manager = TradeOfferManager::builder()
+            .api_key("WRONG".into())
+            .community_url(steam.url())
+            .web_api_url(steam.url())
+            .poll_data_store(MemoryPollDataStore::new())
+            .build();
+        
+        assert!(manager.get_active_trade_offers().await.is_err());
+        assert_eq!(steam.state().request_count(FakeRoute::GetTradeOffers), 1);
+    }
+}
@@ -0,0 +1,838 @@
+use crate::SteamID;
+use crate::api::response::{RawTradeOffer, RawAsset};
+use crate::enums::{TradeOfferState, ConfirmationMethod};
+use crate::request::NewTradeOfferItem;
+use crate::response::ClassInfo;
+use crate::types::{AppId, ContextId, ClassId, InstanceId, TradeOfferId};
+use crate::time;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// The API key accepted by [`FakeSteam`][super::FakeSteam].
+pub const FAKE_API_KEY: &str = "FAKEAPIKEY0000000000000000000000";
+
+// How long offers last before they expire.
+const OFFER_EXPIRATION_DAYS: i64 = 14;
+
+/// The endpoints served by [`FakeSteam`][super::FakeSteam].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FakeRoute {
+    /// `POST /tradeoffer/new/send`
+    SendOffer,
+    /// `POST /tradeoffer/{tradeofferid}/accept`
+    AcceptOffer,
+    /// `POST /tradeoffer/{tradeofferid}/decline`
+    DeclineOffer,
+    /// `POST /tradeoffer/{tradeofferid}/cancel`
+    CancelOffer,
+    /// `GET /inventory/{steamid}/{appid}/{contextid}`
+    Inventory,
+    /// `GET /mobileconf/getlist`
+    GetConfirmations,
+    /// `GET /mobileconf/ajaxop`
+    SendConfirmation,
+    /// `GET /IEconService/GetTradeOffers/v1`
+    GetTradeOffers,
+    /// `GET /IEconService/GetTradeOffer/v1`
+    GetTradeOffer,
+    /// `GET /ISteamEconomy/GetAssetClassInfo/v1`
+    GetAssetClassInfo,
+}
+
+impl FakeRoute {
+    fn from_request(
+        method: &str,
+        segments: &[&str],
+    ) -> Option<Self> {
+        let route = match (method, segments) {
+            ("POST", ["tradeoffer", "new", "send"]) => Self::SendOffer,
+            ("POST", ["tradeoffer", _, "accept"]) => Self::AcceptOffer,
+            ("POST", ["tradeoffer", _, "decline"]) => Self::DeclineOffer,
+            ("POST", ["tradeoffer", _, "cancel"]) => Self::CancelOffer,
+            ("GET", ["inventory", _, _, _]) => Self::Inventory,
+            ("GET", ["mobileconf", "getlist"]) => Self::GetConfirmations,
+            ("GET", ["mobileconf", "ajaxop"]) => Self::SendConfirmation,
+            ("GET", ["IEconService", "GetTradeOffers", "v1"]) => Self::GetTradeOffers,
+            ("GET", ["IEconService", "GetTradeOffer", "v1"]) => Self::GetTradeOffer,
+            ("GET", ["ISteamEconomy", "GetAssetClassInfo", "v1"]) => Self::GetAssetClassInfo,
+            _ => return None,
+        };
+        
+        Some(route)
+    }
+    
+    fn is_web_api(&self) -> bool {
+        matches!(self, Self::GetTradeOffers | Self::GetTradeOffer | Self::GetAssetClassInfo)
+    }
+}
+
+/// Converts an offer to JSON as returned by the Steam Web API, which omits empty fields rather 
+/// than including them as `null` and uses `0` for no escrow end date.
+pub(crate) fn offer_json(offer: &RawTradeOffer) -> Value {
+    let mut value = serde_json::to_value(offer).unwrap_or_default();
+    
+    if let Value::Object(map) = &mut value {
+        map.retain(|_key, value| !value.is_null());
+        map.entry("escrow_end_date").or_insert(json!(0));
+    }
+    
+    value
+}
+
+/// A scripted failure returned by [`FakeSteam`][super::FakeSteam] in place of a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FakeFailure {
+    /// Responds with the given HTTP status code.
+    Status(u16),
+    /// Responds with a trade offer error page containing the given EResult code, e.g. `16` for
+    /// [`TradeOfferError::Timeout`][crate::error::TradeOfferError::Timeout].
+    TradeOfferError(u32),
+}
+
+impl FakeFailure {
+    fn response(&self) -> FakeResponse {
+        match self {
+            Self::Status(status) => FakeResponse::status(*status),
+            Self::TradeOfferError(code) => FakeResponse::trade_offer_error(*code),
+        }
+    }
+}
+
+/// A request received by [`FakeSteam`][super::FakeSteam].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakeRequest {
+    /// The HTTP method.
+    pub method: String,
+    /// The path of the URL.
+    pub path: String,
+    /// The route the request matched. `None` if the request did not match any route.
+    pub route: Option<FakeRoute>,
+}
+
+#[derive(Debug)]
+struct ScriptedFailure {
+    route: FakeRoute,
+    failure: FakeFailure,
+    after_applying: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct FakeResponse {
+    pub(crate) status: u16,
+    pub(crate) content_type: &'static str,
+    pub(crate) body: String,
+}
+
+impl FakeResponse {
+    fn json(value: Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string(),
+        }
+    }
+    
+    fn status(status: u16) -> Self {
+        Self {
+            status,
+            content_type: "text/html",
+            body: String::new(),
+        }
+    }
+    
+    fn trade_offer_error(code: u32) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/html",
+            body: format!(r#"<div id="error_msg">There was an error with your trade offer. Please try again later. ({code})</div>"#),
+        }
+    }
+}
+
+/// A pending mobile confirmation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakeConfirmation {
+    /// The ID of the confirmation.
+    pub id: u64,
+    /// The nonce.
+    pub nonce: u64,
+    /// The offer the confirmation is for.
+    pub tradeofferid: TradeOfferId,
+}
+
+/// The state of [`FakeSteam`][super::FakeSteam]. Requests act on this state, and it can be
+/// changed directly to script what Steam does.
+#[derive(Debug)]
+pub struct FakeSteamState {
+    steamid: SteamID,
+    mobile_confirmation_required: bool,
+    next_id: u64,
+    offers: BTreeMap<TradeOfferId, RawTradeOffer>,
+    /// Inventories keyed by account ID.
+    inventories: HashMap<(u32, AppId, ContextId), Vec<RawAsset>>,
+    classinfos: HashMap<(AppId, ClassId, InstanceId), ClassInfo>,
+    confirmations: Vec<FakeConfirmation>,
+    failures: VecDeque<ScriptedFailure>,
+    requests: Vec<FakeRequest>,
+}
+
+impl FakeSteamState {
+    pub(crate) fn new(steamid: SteamID) -> Self {
+        Self {
+            steamid,
+            mobile_confirmation_required: false,
+            next_id: 1000,
+            offers: BTreeMap::new(),
+            inventories: HashMap::new(),
+            classinfos: HashMap::new(),
+            confirmations: Vec::new(),
+            failures: VecDeque::new(),
+            requests: Vec::new(),
+        }
+    }
+    
+    /// The [`SteamID`] of the logged in account.
+    pub fn steamid(&self) -> SteamID {
+        self.steamid
+    }
+    
+    /// Whether sending and accepting offers requires mobile confirmation. Sent offers are created
+    /// in [`TradeOfferState::CreatedNeedsConfirmation`] until confirmed. Default is `false`.
+    pub fn set_mobile_confirmation_required(&mut self, mobile_confirmation_required: bool) {
+        self.mobile_confirmation_required = mobile_confirmation_required;
+    }
+    
+    /// Adds a classinfo which is served from `GetAssetClassInfo` and inventories.
+    pub fn add_classinfo(&mut self, appid: AppId, classinfo: ClassInfo) {
+        self.classinfos.insert((appid, classinfo.classid, classinfo.instanceid), classinfo);
+    }
+    
+    /// Adds an item to the inventory of `steamid` using `classinfo` for its description. Returns
+    /// the created asset.
+    pub fn add_item(
+        &mut self,
+        steamid: SteamID,
+        appid: AppId,
+        contextid: ContextId,
+        classinfo: ClassInfo,
+    ) -> RawAsset {
+        let asset = RawAsset {
+            appid,
+            contextid,
+            assetid: self.next_id(),
+            amount: 1,
+            missing: false,
+            classid: classinfo.classid,
+            instanceid: classinfo.instanceid,
+        };
+        
+        self.add_classinfo(appid, classinfo);
+        self.inventories
+            .entry((steamid.account_id(), appid, contextid))
+            .or_default()
+            .push(asset);
+        asset
+    }
+    
+    /// Gets the inventory of `steamid`.
+    pub fn inventory(
+        &self,
+        steamid: SteamID,
+        appid: AppId,
+        contextid: ContextId,
+    ) -> Vec<RawAsset> {
+        self.inventories
+            .get(&(steamid.account_id(), appid, contextid))
+            .cloned()
+            .unwrap_or_default()
+    }
+    
+    /// Adds an offer sent to us from `partner`. `items_to_give` are the items we give and
+    /// `items_to_receive` are the items we receive. Returns the ID of the offer.
+    pub fn receive_offer(
+        &mut self,
+        partner: SteamID,
+        items_to_give: Vec<RawAsset>,
+        items_to_receive: Vec<RawAsset>,
+    ) -> TradeOfferId {
+        let now = time::get_server_time_now();
+        let tradeofferid = self.next_id();
+        
+        self.offers.insert(tradeofferid, RawTradeOffer {
+            tradeofferid,
+            tradeid: None,
+            accountid_other: partner.account_id(),
+            message: None,
+            items_to_receive,
+            items_to_give,
+            is_our_offer: false,
+            from_real_time_trade: false,
+            expiration_time: now + chrono::Duration::try_days(OFFER_EXPIRATION_DAYS).unwrap_or_default(),
+            time_created: now,
+            time_updated: now,
+            trade_offer_state: TradeOfferState::Active,
+            escrow_end_date: None,
+            confirmation_method: ConfirmationMethod::None,
+        });
+        tradeofferid
+    }
+    
+    /// Adds or replaces an offer.
+    pub fn insert_offer(&mut self, offer: RawTradeOffer) {
+        self.offers.insert(offer.tradeofferid, offer);
+    }
+    
+    /// Gets an offer.
+    pub fn offer(&self, tradeofferid: TradeOfferId) -> Option<&RawTradeOffer> {
+        self.offers.get(&tradeofferid)
+    }
+    
+    /// Gets every offer.
+    pub fn offers(&self) -> Vec<&RawTradeOffer> {
+        self.offers.values().collect()
+    }
+    
+    /// Changes the state of an offer, e.g. to simulate the partner accepting or declining it.
+    /// Items are not moved between inventories.
+    pub fn set_offer_state(
+        &mut self,
+        tradeofferid: TradeOfferId,
+        trade_offer_state: TradeOfferState,
+    ) {
+        if let Some(offer) = self.offers.get_mut(&tradeofferid) {
+            offer.trade_offer_state = trade_offer_state;
+            offer.time_updated = time::get_server_time_now();
+        }
+    }
+    
+    /// Gets the pending mobile confirmations.
+    pub fn confirmations(&self) -> &[FakeConfirmation] {
+        &self.confirmations
+    }
+    
+    /// Responds to the next request to `route` with `failure` without acting on it.
+    pub fn fail_next(&mut self, route: FakeRoute, failure: FakeFailure) {
+        self.failures.push_back(ScriptedFailure {
+            route,
+            failure,
+            after_applying: false,
+        });
+    }
+    
+    /// Acts on the next request to `route` but responds with `failure`, e.g. to simulate an offer
+    /// which was sent even though Steam responded with an error.
+    pub fn fail_next_after_applying(&mut self, route: FakeRoute, failure: FakeFailure) {
+        self.failures.push_back(ScriptedFailure {
+            route,
+            failure,
+            after_applying: true,
+        });
+    }
+    
+    /// Gets every request received.
+    pub fn requests(&self) -> &[FakeRequest] {
+        &self.requests
+    }
+    
+    /// Gets the number of requests received for `route`.
+    pub fn request_count(&self, route: FakeRoute) -> usize {
+        self.requests
+            .iter()
+            .filter(|request| request.route == Some(route))
+            .count()
+    }
+    
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+    
+    pub(crate) fn handle(
+        &mut self,
+        method: &str,
+        path: &str,
+        query: &HashMap<String, String>,
+        form: &HashMap<String, String>,
+    ) -> FakeResponse {
+        let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+        let route = FakeRoute::from_request(method, &segments);
+        
+        self.requests.push(FakeRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            route,
+        });
+        
+        let Some(route) = route else {
+            return FakeResponse::status(404);
+        };
+        
+        if route.is_web_api() && query.get("key").map(String::as_str) != Some(FAKE_API_KEY) {
+            return FakeResponse::status(403);
+        }
+        
+        let failure = self.failures
+            .iter()
+            .position(|failure| failure.route == route)
+            .and_then(|index| self.failures.remove(index));
+        
+        match failure {
+            Some(failure) => {
+                if failure.after_applying {
+                    self.respond(route, &segments, query, form);
+                }
+                
+                failure.failure.response()
+            },
+            None => self.respond(route, &segments, query, form),
+        }
+    }
+    
+    fn respond(
+        &mut self,
+        route: FakeRoute,
+        segments: &[&str],
+        query: &HashMap<String, String>,
+        form: &HashMap<String, String>,
+    ) -> FakeResponse {
+        let path_id = |index: usize| segments.get(index).and_then(|id| id.parse::<u64>().ok());
+        
+        match route {
+            FakeRoute::SendOffer => self.send_offer(form),
+            FakeRoute::AcceptOffer => self.accept_offer(path_id(1)),
+            FakeRoute::DeclineOffer => self.decline_offer(path_id(1)),
+            FakeRoute::CancelOffer => self.cancel_offer(path_id(1)),
+            FakeRoute::Inventory => self.get_inventory(path_id(1), path_id(2), path_id(3), query),
+            FakeRoute::GetConfirmations => self.get_confirmations(),
+            FakeRoute::SendConfirmation => self.send_confirmation(query),
+            FakeRoute::GetTradeOffers => self.get_trade_offers(query),
+            FakeRoute::GetTradeOffer => self.get_trade_offer(query),
+            FakeRoute::GetAssetClassInfo => self.get_asset_classinfo(query),
+        }
+    }
+    
+    /// Finds the assets for `items` in the inventories of `accountid`.
+    fn find_assets(
+        &self,
+        accountid: u32,
+        items: &[NewTradeOfferItem],
+    ) -> Option<Vec<RawAsset>> {
+        items
+            .iter()
+            .map(|item| {
+                let asset = self.inventories
+                    .get(&(accountid, item.appid, item.contextid))?
+                    .iter()
+                    .find(|asset| asset.assetid == item.assetid)?;
+                
+                Some(RawAsset {
+                    amount: item.amount,
+                    ..*asset
+                })
+            })
+            .collect()
+    }
+    
+    fn add_confirmation(&mut self, tradeofferid: TradeOfferId) {
+        if self.confirmations.iter().any(|confirmation| confirmation.tradeofferid == tradeofferid) {
+            return;
+        }
+        
+        let id = self.next_id();
+        let nonce = self.next_id();
+        
+        self.confirmations.push(FakeConfirmation {
+            id,
+            nonce,
+            tradeofferid,
+        });
+    }
+    
+    /// Accepts an offer and moves its items between inventories.
+    fn complete_trade(&mut self, tradeofferid: TradeOfferId) {
+        let our_accountid = self.steamid.account_id();
+        let tradeid = self.next_id();
+        let Some(offer) = self.offers.get_mut(&tradeofferid) else {
+            return;
+        };
+        
+        offer.trade_offer_state = TradeOfferState::Accepted;
+        offer.tradeid = Some(tradeid.into());
+        offer.time_updated = time::get_server_time_now();
+        
+        let partner_accountid = offer.accountid_other;
+        let transfers = offer.items_to_give
+            .iter()
+            .map(|asset| (*asset, our_accountid, partner_accountid))
+            .chain(offer.items_to_receive
+                .iter()
+                .map(|asset| (*asset, partner_accountid, our_accountid)))
+            .collect::<Vec<_>>();
+        
+        for (asset, from, to) in transfers {
+            if let Some(inventory) = self.inventories.get_mut(&(from, asset.appid, asset.contextid)) {
+                inventory.retain(|item| item.assetid != asset.assetid);
+            }
+            
+            self.inventories
+                .entry((to, asset.appid, asset.contextid))
+                .or_default()
+                .push(asset);
+        }
+    }
+    
+    fn send_offer(&mut self, form: &HashMap<String, String>) -> FakeResponse {
+        #[derive(Deserialize)]
+        struct OfferFormUser {
+            assets: Vec<NewTradeOfferItem>,
+        }
+        
+        #[derive(Deserialize)]
+        struct OfferForm {
+            me: OfferFormUser,
+            them: OfferFormUser,
+        }
+        
+        let partner = form.get("partner").and_then(|partner| partner.parse::<u64>().ok());
+        let offer_form = form.get("json_tradeoffer")
+            .and_then(|json| serde_json::from_str::<OfferForm>(json).ok());
+        let (Some(partner), Some(offer_form)) = (partner, offer_form) else {
+            return FakeResponse::status(400);
+        };
+        let partner_accountid = SteamID::from(partner).account_id();
+        let items_to_give = self.find_assets(self.steamid.account_id(), &offer_form.me.assets);
+        let items_to_receive = self.find_assets(partner_accountid, &offer_form.them.assets);
+        let (Some(items_to_give), Some(items_to_receive)) = (items_to_give, items_to_receive) else {
+            // The items do not exist in their inventories.
+            return FakeResponse::trade_offer_error(26);
+        };
+        let now = time::get_server_time_now();
+        let tradeofferid = self.next_id();
+        let needs_confirmation = self.mobile_confirmation_required;
+        
+        self.offers.insert(tradeofferid, RawTradeOffer {
+            tradeofferid,
+            tradeid: None,
+            accountid_other: partner_accountid,
+            message: form.get("tradeoffermessage").filter(|message| !message.is_empty()).cloned(),
+            items_to_receive,
+            items_to_give,
+            is_our_offer: true,
+            from_real_time_trade: false,
+            expiration_time: now + chrono::Duration::try_days(OFFER_EXPIRATION_DAYS).unwrap_or_default(),
+            time_created: now,
+            time_updated: now,
+            trade_offer_state: if needs_confirmation {
+                TradeOfferState::CreatedNeedsConfirmation
+            } else {
+                TradeOfferState::Active
+            },
+            escrow_end_date: None,
+            confirmation_method: if needs_confirmation {
+                ConfirmationMethod::MobileApp
+            } else {
+                ConfirmationMethod::None
+            },
+        });
+        
+        if let Some(countered) = form.get("tradeofferid_countered").and_then(|id| id.parse::<u64>().ok()) {
+            self.set_offer_state(countered, TradeOfferState::Countered);
+        }
+        
+        if needs_confirmation {
+            self.add_confirmation(tradeofferid);
+        }
+        
+        FakeResponse::json(json!({
+            "tradeofferid": tradeofferid.to_string(),
+            "needs_mobile_confirmation": needs_confirmation,
+            "needs_email_confirmation": false,
+        }))
+    }
+    
+    fn accept_offer(&mut self, tradeofferid: Option<TradeOfferId>) -> FakeResponse {
+        let Some(offer) = tradeofferid.and_then(|id| self.offers.get(&id)) else {
+            return FakeResponse::trade_offer_error(2);
+        };
+        
+        if offer.is_our_offer || offer.trade_offer_state != TradeOfferState::Active {
+            return FakeResponse::trade_offer_error(11);
+        }
+        
+        let tradeofferid = offer.tradeofferid;
+        
+        if self.mobile_confirmation_required {
+            self.add_confirmation(tradeofferid);
+            
+            return FakeResponse::json(json!({
+                "needs_mobile_confirmation": true,
+                "needs_email_confirmation": false,
+            }));
+        }
+        
+        self.complete_trade(tradeofferid);
+        
+        let tradeid = self.offers.get(&tradeofferid).and_then(|offer| offer.tradeid);
+        
+        FakeResponse::json(json!({
+            "tradeid": tradeid.map(|tradeid| tradeid.to_string()),
+        }))
+    }
+    
+    fn decline_offer(&mut self, tradeofferid: Option<TradeOfferId>) -> FakeResponse {
+        let Some(offer) = tradeofferid.and_then(|id| self.offers.get(&id)) else {
+            return FakeResponse::trade_offer_error(2);
+        };
+        
+        if offer.is_our_offer || offer.trade_offer_state != TradeOfferState::Active {
+            return FakeResponse::trade_offer_error(11);
+        }
+        
+        let tradeofferid = offer.tradeofferid;
+        
+        self.set_offer_state(tradeofferid, TradeOfferState::Declined);
+        FakeResponse::json(json!({
+            "tradeofferid": tradeofferid.to_string(),
+        }))
+    }
+    
+    fn cancel_offer(&mut self, tradeofferid: Option<TradeOfferId>) -> FakeResponse {
+        let Some(offer) = tradeofferid.and_then(|id| self.offers.get(&id)) else {
+            return FakeResponse::trade_offer_error(2);
+        };
+        
+        if !offer.is_our_offer || !matches!(
+            offer.trade_offer_state,
+            TradeOfferState::Active | TradeOfferState::CreatedNeedsConfirmation,
+        ) {
+            return FakeResponse::trade_offer_error(11);
+        }
+        
+        let tradeofferid = offer.tradeofferid;
+        
+        self.set_offer_state(tradeofferid, TradeOfferState::Canceled);
+        self.confirmations.retain(|confirmation| confirmation.tradeofferid != tradeofferid);
+        FakeResponse::json(json!({
+            "tradeofferid": tradeofferid.to_string(),
+        }))
+    }
+    
+    fn get_inventory(
+        &self,
+        steamid: Option<u64>,
+        appid: Option<u64>,
+        contextid: Option<u64>,
+        query: &HashMap<String, String>,
+    ) -> FakeResponse {
+        let (Some(steamid), Some(appid), Some(contextid)) = (steamid, appid, contextid) else {
+            return FakeResponse::status(400);
+        };
+        let appid = appid as AppId;
+        let inventory = self.inventory(SteamID::from(steamid), appid, contextid);
+        let count = query.get("count")
+            .and_then(|count| count.parse::<usize>().ok())
+            .unwrap_or(inventory.len())
+            .max(1);
+        let start = query.get("start_assetid")
+            .and_then(|assetid| assetid.parse::<u64>().ok())
+            .and_then(|assetid| inventory.iter().position(|asset| asset.assetid == assetid))
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        let assets = inventory
+            .iter()
+            .skip(start)
+            .take(count)
+            .collect::<Vec<_>>();
+        let more_items = start + assets.len() < inventory.len();
+        let descriptions = assets
+            .iter()
+            .filter_map(|asset| self.description(&(appid, asset.classid, asset.instanceid)))
+            .collect::<Vec<_>>();
+        let mut body = json!({
+            "success": 1,
+            "assets": assets,
+            "descriptions": descriptions,
+            "total_inventory_count": inventory.len(),
+        });
+        
+        if more_items {
+            body["more_items"] = json!(1);
+            body["last_assetid"] = json!(assets.last().map(|asset| asset.assetid.to_string()));
+        }
+        
+        FakeResponse::json(body)
+    }
+    
+    fn get_confirmations(&self) -> FakeResponse {
+        let conf = self.confirmations
+            .iter()
+            .map(|confirmation| json!({
+                "type": 2,
+                "type_name": "Trade Offer",
+                "id": confirmation.id.to_string(),
+                "creator_id": confirmation.tradeofferid.to_string(),
+                "nonce": confirmation.nonce.to_string(),
+                "creation_time": time::get_server_time_now().timestamp(),
+                "cancel": "Cancel",
+                "accept": "Accept",
+                "multi": false,
+                "headline": "Trade offer",
+                "summary": [],
+            }))
+            .collect::<Vec<_>>();
+        
+        FakeResponse::json(json!({
+            "success": true,
+            "conf": conf,
+        }))
+    }
+    
+    fn send_confirmation(&mut self, query: &HashMap<String, String>) -> FakeResponse {
+        let param = |key: &str| query.get(key).and_then(|value| value.parse::<u64>().ok());
+        let (Some(id), Some(nonce)) = (param("cid"), param("ck")) else {
+            return FakeResponse::json(json!({ "success": false }));
+        };
+        let Some(index) = self.confirmations
+            .iter()
+            .position(|confirmation| confirmation.id == id && confirmation.nonce == nonce) else {
+            return FakeResponse::json(json!({ "success": false }));
+        };
+        let confirmation = self.confirmations.remove(index);
+        let Some(offer) = self.offers.get(&confirmation.tradeofferid) else {
+            return FakeResponse::json(json!({ "success": false }));
+        };
+        let (is_our_offer, trade_offer_state) = (offer.is_our_offer, offer.trade_offer_state);
+        
+        match query.get("op").map(String::as_str) {
+            Some("allow") if is_our_offer => if trade_offer_state == TradeOfferState::CreatedNeedsConfirmation {
+                self.set_offer_state(confirmation.tradeofferid, TradeOfferState::Active);
+            },
+            Some("allow") => if trade_offer_state == TradeOfferState::Active {
+                self.complete_trade(confirmation.tradeofferid);
+            },
+            Some("cancel") if is_our_offer => {
+                self.set_offer_state(confirmation.tradeofferid, TradeOfferState::CanceledBySecondFactor);
+            },
+            Some("cancel") => {},
+            _ => return FakeResponse::json(json!({ "success": false })),
+        }
+        
+        FakeResponse::json(json!({ "success": true }))
+    }
+    
+    /// Gets a classinfo as a description including its app ID.
+    fn description(&self, class: &(AppId, ClassId, InstanceId)) -> Option<Value> {
+        let classinfo = self.classinfos.get(class)?;
+        let mut value = serde_json::to_value(classinfo).ok()?;
+        
+        value["appid"] = json!(class.0);
+        Some(value)
+    }
+    
+    /// Gets the classinfos for the items in `offers` as descriptions.
+    fn offer_descriptions(&self, offers: &[&RawTradeOffer]) -> Vec<Value> {
+        let mut classes = offers
+            .iter()
+            .flat_map(|offer| offer.items_to_give.iter().chain(offer.items_to_receive.iter()))
+            .map(|asset| (asset.appid, asset.classid, asset.instanceid))
+            .collect::<Vec<_>>();
+        
+        classes.sort_unstable();
+        classes.dedup();
+        classes
+            .into_iter()
+            .filter_map(|class| self.description(&class))
+            .collect()
+    }
+    
+    fn get_trade_offers(&self, query: &HashMap<String, String>) -> FakeResponse {
+        let flag = |key: &str| query.get(key).map(String::as_str) == Some("true");
+        let cutoff = query.get("time_historical_cutoff")
+            .and_then(|cutoff| cutoff.parse::<i64>().ok())
+            .map(time::timestamp_to_server_time);
+        let is_active = |offer: &RawTradeOffer| matches!(
+            offer.trade_offer_state,
+            TradeOfferState::Active | TradeOfferState::CreatedNeedsConfirmation | TradeOfferState::InEscrow,
+        );
+        let offers = self.offers
+            .values()
+            .filter(|offer| {
+                if flag("active_only") {
+                    is_active(offer) || cutoff.is_some_and(|cutoff| offer.time_updated >= cutoff)
+                } else if flag("historical_only") {
+                    !is_active(offer)
+                } else {
+                    true
+                }
+            })
+            .collect::<Vec<_>>();
+        let (sent, received): (Vec<&RawTradeOffer>, Vec<&RawTradeOffer>) = offers
+            .into_iter()
+            .partition(|offer| offer.is_our_offer);
+        let sent = if flag("get_sent_offers") { sent } else { Vec::new() };
+        let received = if flag("get_received_offers") { received } else { Vec::new() };
+        let mut response = json!({
+            "trade_offers_sent": sent.iter().map(|offer| offer_json(offer)).collect::<Vec<_>>(),
+            "trade_offers_received": received.iter().map(|offer| offer_json(offer)).collect::<Vec<_>>(),
+            "next_cursor": 0,
+        });
+        
+        if flag("get_descriptions") {
+            let offers = sent.iter().chain(received.iter()).copied().collect::<Vec<_>>();
+            
+            response["descriptions"] = json!(self.offer_descriptions(&offers));
+        }
+        
+        FakeResponse::json(json!({
+            "response": response,
+        }))
+    }
+    
+    fn get_trade_offer(&self, query: &HashMap<String, String>) -> FakeResponse {
+        let offer = query.get("tradeofferid")
+            .and_then(|id| id.parse::<u64>().ok())
+            .and_then(|id| self.offers.get(&id));
+        
+        match offer {
+            Some(offer) => FakeResponse::json(json!({
+                "response": {
+                    "offer": offer_json(offer),
+                },
+            })),
+            None => FakeResponse::json(json!({
+                "response": {},
+            })),
+        }
+    }
+    
+    fn get_asset_classinfo(&self, query: &HashMap<String, String>) -> FakeResponse {
+        let param = |key: &str| query.get(key).and_then(|value| value.parse::<u64>().ok());
+        let appid = param("appid").unwrap_or_default() as AppId;
+        let class_count = param("class_count").unwrap_or_default();
+        let mut result = serde_json::Map::new();
+        
+        result.insert("success".into(), json!(true));
+        
+        for i in 0..class_count {
+            let Some(classid) = param(&format!("classid{i}")) else {
+                continue;
+            };
+            let instanceid = param(&format!("instanceid{i}"));
+            let key = match instanceid {
+                Some(instanceid) => format!("{classid}_{instanceid}"),
+                None => classid.to_string(),
+            };
+            
+            if let Some(classinfo) = self.classinfos.get(&(appid, classid, instanceid)) {
+                if let Ok(value) = serde_json::to_value(classinfo) {
+                    result.insert(key, value);
+                }
+            }
+        }
+        
+        FakeResponse::json(json!({
+            "result": result,
+        }))
+    }
+}